@@ -1,743 +1,2637 @@
-use crate::defs::{DataType, Expression, ExpressionType, Program};
-use crate::defs::{Parameter, Statement, StatementOptions, StatementType, TokenType};
-use crate::defs::{BINARY_OPERATORS, DATATYPES, EXPRESSION_DELIMITERS};
-use crate::utils::{get_datatype_from_str, get_token_type};
-
-pub(crate) fn generate_ast(tokens: &Vec<&str>) -> Program {
-    let mut statements: Vec<Statement> = vec![];
+use crate::defs::{
+    ArmBody, ArrayType, Associativity, DataType, EnumDecl, Expr, ExprKind, Field, FnDecl,
+    LambdaBody, LexError, MatchArm, Parameter, Pattern, StructDecl,
+};
+use crate::defs::{ParseError, Program, Stmt, StmtKind, Token, TokenType, VarDecl};
+use crate::lexer::TokenStream;
+use crate::utils::get_datatype_from_str;
+use phf::phf_map;
+use std::ops::Range;
+
+pub(crate) fn generate_ast(tokens: &[Token]) -> Result<Program, ParseError> {
+    let mut tokens: TokenStream = TokenStream::init(strip_trivia(tokens).into_iter().map(Ok));
+    let mut statements: Vec<Stmt> = vec![];
 
     // Parse all Statements from Tokens
-    let mut index: usize = 0;
-    while index < tokens.len() {
-        statements.push(get_next_statement(&tokens, &mut index));
+    while tokens.peek()?.is_some() {
+        statements.push(parse_statement(&mut tokens)?);
     }
-    return Program {
+    return Ok(Program {
         statements: statements,
-    };
-
-    fn get_next_statement(tokens: &Vec<&str>, index: &mut usize) -> Statement {
-        let token: &str = tokens.get(*index).unwrap();
+    });
+}
 
-        // Statements with known first Token
-        if token == ";" {
-            return get_no_operation_statement(index);
-        }
-        if token == "{" {
-            return get_compound_statement(tokens, index);
-        }
-        if token == "fun" {
-            return get_function_statement(tokens, index);
-        }
-        if token == "if" {
-            return get_block_statement(tokens, index, "if");
-        }
-        if token == "elif" {
-            return get_block_statement(tokens, index, "elif");
-        }
-        if token == "else" {
-            todo!("Parsing 'else' statements are not implemented")
-        }
-        if token == "return" {
-            return get_return_statement(tokens, index);
-        }
-        if token == "while" {
-            return get_block_statement(tokens, index, "while");
+// Parse all Statements, skipping to the next statement boundary after each error
+pub(crate) fn generate_ast_recover(tokens: &[Token]) -> (Program, Vec<ParseError>) {
+    let mut tokens: TokenStream = TokenStream::init(strip_trivia(tokens).into_iter().map(Ok));
+    let mut statements: Vec<Stmt> = vec![];
+    let mut errors: Vec<ParseError> = vec![];
+    while let Ok(Some(_)) = tokens.peek() {
+        match parse_statement(&mut tokens) {
+            Ok(statement) => statements.push(statement),
+            Err(error) => {
+                errors.push(error);
+                synchronize(&mut tokens);
+            }
         }
+    }
+    let program: Program = Program {
+        statements: statements,
+    };
+    return (program, errors);
+}
 
-        // Variable Definition
-        if DATATYPES.contains(&token) {
-            let variable_datatype: DataType = get_datatype_from_str(token);
-            return get_variable_definition_statement(tokens, index, variable_datatype);
+// Drop whitespace and comments, except doc comments right before a declaration. Consecutive
+// doc comments are merged into one Token with their text as the value
+fn strip_trivia(tokens: &[Token]) -> Vec<Token> {
+    let mut stripped: Vec<Token> = vec![];
+    let mut doc: Option<Token> = None;
+    for token in tokens.iter() {
+        if token.token_type != TokenType::None {
+            let documented: bool = matches!(token.lexeme.as_str(), "fun" | "let" | "const");
+            if let Some(doc) = doc.take().filter(|_| documented) {
+                stripped.push(doc);
+            }
+            stripped.push(token.clone());
+            continue;
         }
 
-        // Expression
-        return get_expression_statement(tokens, index);
+        // Whitespace keeps the doc comment before it, other comments detach it
+        match (get_doc_text(token.lexeme.as_str()), &mut doc) {
+            (Some(text), Some(doc)) => {
+                let value: &mut String = doc.value.get_or_insert_with(String::new);
+                value.push('\n');
+                value.push_str(&text);
+                doc.span.end = token.span.end;
+            }
+            (Some(text), None) => {
+                doc = Some(Token {
+                    value: Some(text),
+                    ..token.clone()
+                })
+            }
+            (None, _) if token.lexeme.as_str().trim().is_empty() => {}
+            (None, _) => doc = None,
+        }
     }
+    return stripped;
+}
 
-    fn get_no_operation_statement(index: &mut usize) -> Statement {
-        *index += 1; // Go past the semicolon
-        Statement::new(StatementType::NoOperation, None, None, None, None)
+// Text of '/// text' and '/** text */' comments, other trivia has none
+fn get_doc_text(lexeme: &str) -> Option<String> {
+    if lexeme.starts_with("///") && !lexeme.starts_with("////") {
+        let text: &str = &lexeme[3..];
+        return Some(
+            text.strip_prefix(' ')
+                .unwrap_or(text)
+                .trim_end()
+                .to_string(),
+        );
     }
+    if lexeme.starts_with("/**") && !lexeme.starts_with("/***") && lexeme.len() > 4 {
+        // Lines of block doc comments may start with '*' to line them up
+        let lines: Vec<&str> = lexeme[3..lexeme.len() - 2]
+            .lines()
+            .map(|line| line.trim())
+            .map(|line| line.strip_prefix('*').unwrap_or(line).trim_start())
+            .collect();
+        return Some(lines.join("\n").trim().to_string());
+    }
+    return None;
+}
 
-    fn get_compound_statement(tokens: &Vec<&str>, index: &mut usize) -> Statement {
-        *index += 1; // Go past the open curly brace '{'
-        if index >= &mut tokens.len() {
-            panic!("Program cannot end to open curly brace")
+// Skip Tokens up to and including the next ';' or '}'
+fn synchronize(tokens: &mut TokenStream) {
+    while let Ok(Some(token)) = tokens.next() {
+        if token.token_type == TokenType::Delimiter && (token.lexeme == ";" || token.lexeme == "}")
+        {
+            return;
         }
+    }
+}
 
-        // Get all Statements inside the Compound Statement
-        let mut statements: Vec<Statement> = vec![];
-        let mut token: &str = tokens.get(*index).expect("Unclosed Compound Statement"); // TODO: Enhance error reporting
-
-        // Closing curly bracket ends the compound statement
-        while token != "}" {
-            statements.push(get_next_statement(tokens, index));
-            token = tokens
-                .get(*index)
-                .expect("Unexpected EOF: Unclosed Compound Statement"); // TODO: Enhance error reporting
-        }
+fn parse_statement(tokens: &mut TokenStream) -> Result<Stmt, ParseError> {
+    // Doc comments left by strip_trivia belong to the declaration after them
+    let doc: Option<String> = match tokens.next_if_type(TokenType::None)? {
+        Some(comment) => comment.value,
+        None => None,
+    };
 
-        // Go past the closing curly brace '}'
-        eat_token("}", tokens, index);
-        return Statement::new(StatementType::Compound, None, None, None, Some(statements));
+    let token: &Token = tokens
+        .peek()?
+        .expect("Statements are only parsed before EOF");
+    let start: usize = token.span.start;
+    let mut kind: StmtKind = parse_statement_kind(tokens)?;
+    match &mut kind {
+        StmtKind::FnDecl(function) => function.doc = doc,
+        StmtKind::VarDecl(declaration) => declaration.doc = doc,
+        _ => {}
     }
+    return Ok(Stmt::init(kind, start..tokens.previous_end()));
+}
 
-    fn get_expression_in_round_brackets(tokens: &Vec<&str>, index: &mut usize) -> Expression {
-        // Test if the current Token's value is an opening round bracket '('
-        eat_token("(", tokens, index);
-
-        // Get Expression between round brackets
-        let expression: Expression = get_next_expression(tokens, index);
+fn parse_statement_kind(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    let token: &Token = tokens
+        .peek()?
+        .expect("Statements are only parsed before EOF");
 
-        // Test if the current Token's value is a closing round bracket ')'
-        eat_token(")", tokens, index);
-        return expression;
+    // Variable Definition
+    if token.token_type == TokenType::DataType {
+        return Ok(StmtKind::VarDecl(parse_variable_definition(tokens)?));
     }
 
-    fn get_function_statement(tokens: &Vec<&str>, index: &mut usize) -> Statement {
-        // Get function name from after 'fun' Token
-        let function_name: String = tokens
-            .get(*index + 1)
-            .expect("Expected function name but got nothing")
-            .to_string();
+    // Statements with known first Token
+    match token.lexeme.as_str() {
+        ";" => {
+            tokens.next()?;
+            Ok(StmtKind::NoOperation)
+        }
+        "{" => Ok(StmtKind::Block(parse_block(tokens)?)),
+        "fun" => Ok(StmtKind::FnDecl(parse_function(tokens)?)),
+        "let" | "const" => Ok(StmtKind::VarDecl(parse_variable_declaration(tokens)?)),
+        "if" => parse_conditional(tokens),
+        "elif" | "else" => Err(ParseError {
+            message: format!("Expected 'if' before '{}'", token.lexeme),
+            span: token.span.clone(),
+        }),
+        "break" | "continue" => parse_loop_control(tokens),
+        "return" => parse_return(tokens),
+        "struct" => Ok(StmtKind::StructDecl(parse_struct(tokens)?)),
+        "enum" => Ok(StmtKind::EnumDecl(parse_enum(tokens)?)),
+        "while" => parse_loop(tokens),
+        "for" => parse_for(tokens),
+        _ => parse_expression_statement(tokens),
+    }
+}
 
-        *index += 2; // Go past 'fun' Token and function_name
-        eat_token("(", tokens, index);
+// Blocks are also the bodies of functions, conditionals and loops
+fn parse_block(tokens: &mut TokenStream) -> Result<Vec<Stmt>, ParseError> {
+    return parse_block_with(tokens, false);
+}
 
-        // Gather function parameters
-        let parameters: Vec<Parameter> = get_function_parameters(&function_name, tokens, index);
-        let return_type: DataType = get_function_return_type(tokens, index);
-        return Statement {
-            typ: StatementType::Function,
-            value: Some(function_name),
-            options: Some(StatementOptions {
-                parameters: parameters,
-                return_type: return_type,
-            }),
-            expression: None,
-            statements: Some(vec![get_compound_statement(tokens, index)]),
+// Lambda bodies allow a last Expression without ';' that becomes their return value
+fn parse_block_with(tokens: &mut TokenStream, allow_tail: bool) -> Result<Vec<Stmt>, ParseError> {
+    let opener: Token = tokens.expect_lexeme("{")?;
+
+    // Closing curly bracket ends the block, reaching EOF before it points back to the opener
+    let mut statements: Vec<Stmt> = vec![];
+    while tokens.next_if_lexeme(&["}"])?.is_none() {
+        if tokens.peek()?.is_none() {
+            return Err(ParseError {
+                message: "Expected '}' to close this '{'".to_string(),
+                span: opener.span,
+            });
+        }
+        let statement: Stmt = match tokens.peek()? {
+            Some(token) if allow_tail && starts_expression_statement(token) => {
+                parse_tail_statement(tokens)?
+            }
+            _ => parse_statement(tokens)?,
         };
+        statements.push(statement);
     }
+    return Ok(statements);
+}
 
-    fn get_function_parameters(
-        function_name: &String,
-        tokens: &Vec<&str>,
-        index: &mut usize,
-    ) -> Vec<Parameter> {
-        let mut parameters: Vec<Parameter> = vec![];
-        while *index < tokens.len() {
-            // Example definition:
-            // fun sum(int a, int b) { return a+b; }
+fn starts_expression_statement(token: &Token) -> bool {
+    match token.token_type {
+        TokenType::Keyword => token.lexeme == "match",
+        TokenType::DataType => false,
+        _ => token.lexeme != "{" && token.lexeme != ";",
+    }
+}
 
-            // Get Parameter's DataType
-            let datatype: &str = tokens
-                .get(*index)
-                .expect("Expected parameter datatype but got nothing");
+fn parse_tail_statement(tokens: &mut TokenStream) -> Result<Stmt, ParseError> {
+    let expression: Expr = parse_expression(tokens)?;
+    let start: usize = expression.span().start;
+    let kind: StmtKind = match tokens.peek()? {
+        Some(token) if token.lexeme == "}" => StmtKind::Return(Some(expression)),
+        _ => {
+            tokens.expect_lexeme(";")?;
+            StmtKind::Expression(expression)
+        }
+    };
+    return Ok(Stmt::init(kind, start..tokens.previous_end()));
+}
 
-            // Return if function has no parameters
-            if datatype == ")" {
-                *index += 1;
-                return parameters;
-            }
+fn parse_struct(tokens: &mut TokenStream) -> Result<StructDecl, ParseError> {
+    // Example definition:
+    // struct Point { x: int, y: int }
+    tokens.expect_lexeme("struct")?;
+    let name: Token = tokens.expect(TokenType::Identifier)?;
+    tokens.expect_lexeme("{")?;
+    let mut fields: Vec<Field> = vec![];
+    while tokens.next_if_lexeme(&["}"])?.is_none() {
+        if !fields.is_empty() {
+            tokens.expect_lexeme(",")?;
+        }
+        let field: Token = tokens.expect(TokenType::Identifier)?;
+        if fields
+            .iter()
+            .any(|other| field.lexeme == other.name.as_str())
+        {
+            return Err(ParseError {
+                message: format!(
+                    "Field '{}' is already defined in struct '{}'",
+                    field.lexeme, name.lexeme
+                ),
+                span: field.span,
+            });
+        }
+        tokens.expect_lexeme(":")?;
+        fields.push(Field {
+            name: field.lexeme.to_string(),
+            typ: parse_type(tokens)?,
+        });
+    }
+    return Ok(StructDecl {
+        name: name.lexeme.to_string(),
+        fields: fields,
+    });
+}
 
-            // Test if Token is valid DataType
-            if !DATATYPES.contains(&datatype) {
-                panic!("Expected parameter datatype, got '{}'", datatype);
-            }
+fn parse_enum(tokens: &mut TokenStream) -> Result<EnumDecl, ParseError> {
+    // Example definition:
+    // enum Color { Red, Green, Blue }
+    tokens.expect_lexeme("enum")?;
+    let name: Token = tokens.expect(TokenType::Identifier)?;
+    tokens.expect_lexeme("{")?;
+    let mut variants: Vec<String> = vec![];
+    while tokens.next_if_lexeme(&["}"])?.is_none() {
+        if !variants.is_empty() {
+            tokens.expect_lexeme(",")?;
+        }
+        let variant: Token = tokens.expect(TokenType::Identifier)?;
+        if variants
+            .iter()
+            .any(|other| variant.lexeme == other.as_str())
+        {
+            return Err(ParseError {
+                message: format!(
+                    "Variant '{}' is already defined in enum '{}'",
+                    variant.lexeme, name.lexeme
+                ),
+                span: variant.span,
+            });
+        }
+        variants.push(variant.lexeme.to_string());
+    }
+    return Ok(EnumDecl {
+        name: name.lexeme.to_string(),
+        variants: variants,
+    });
+}
 
-            // Get Parameter's name
-            let parameter_name: String = tokens
-                .get(*index + 1)
-                .expect("Expected parameter name but got nothing")
-                .to_string();
+fn parse_function(tokens: &mut TokenStream) -> Result<FnDecl, ParseError> {
+    // Example definition:
+    // fun sum(a: int, b: int) -> int { return a+b; }
+    tokens.expect_lexeme("fun")?;
+    let name: Token = tokens.expect(TokenType::Identifier)?;
+
+    // Gather function parameters
+    tokens.expect_lexeme("(")?;
+    let mut parameters: Vec<Parameter> = vec![];
+    while tokens.next_if_lexeme(&[")"])?.is_none() {
+        if !parameters.is_empty() {
+            tokens.expect_lexeme(",")?;
+        }
+        let end: usize = tokens.end();
+        let start: usize = tokens.peek()?.map_or(end, |token| token.span.start);
+        let parameter: Parameter = parse_parameter(tokens)?;
+
+        if parameters
+            .last()
+            .is_some_and(|parameter| parameter.variadic)
+        {
+            return Err(ParseError {
+                message: format!(
+                    "Only the last parameter can be variadic but '{}' follows one",
+                    parameter.name
+                ),
+                span: start..tokens.previous_end(),
+            });
+        }
 
-            // Append the parsed parameter to the Parameter list
-            parameters.push(Parameter {
-                name: parameter_name,
-                typ: get_datatype_from_str(datatype),
+        // Only the trailing parameters can be left out of a call
+        let after_default: bool = parameters
+            .iter()
+            .any(|parameter| parameter.default.is_some());
+        if after_default && parameter.default.is_none() && !parameter.variadic {
+            return Err(ParseError {
+                message: format!(
+                    "Parameter '{}' without a default value can not follow one with a default value",
+                    parameter.name
+                ),
+                span: start..tokens.previous_end(),
             });
-            *index += 2; // Go past parameter name
-
-            // After Parameter declaration there should be either ',' or ')'
-            // ',' => There will be more Parameters
-            // ')' => Parameter declarations are over
-            let delimiter: &str = tokens
-                .get(*index)
-                .expect("Expected ',' or ')' but found nothing");
-            if delimiter == "," {
-                *index += 1;
-            } // Go past comma to the next parameter
-        }
-        panic!(
-            "Could not parse parameters for function '{}'",
-            function_name
-        );
-    }
-
-    fn get_function_return_type(tokens: &Vec<&str>, index: &mut usize) -> DataType {
-        let first_token_value: &str = tokens
-            .get(*index)
-            .expect("Deficient Tokens for function declaration");
-        if first_token_value != "->" {
-            if first_token_value != "{" {
-                panic!("Expected '->' or '{}' but got '{}'", "{", first_token_value)
-            }
-            return DataType::None;
-        }
-
-        *index += 1;
-        let datatype_str: &str = tokens
-            .get(*index)
-            .expect("Expected return type but got nothing");
-        let datatype: DataType = get_datatype_from_str(datatype_str);
-
-        // Go over curly brace
-        let open_curly: &str = tokens
-            .get(*index + 1)
-            .expect("Expected open curly bracket for function but got nothing");
-        if open_curly != "{" {
-            panic!(
-                "Expected function's opening curly bracket '{}' but got '{}'",
-                "{", open_curly
-            );
         }
-        *index += 1;
-        return datatype;
+        parameters.push(parameter);
     }
 
-    /// Block statements consist of a Keyword, a condition in round brackets and a Compound Statement
-    fn get_block_statement(tokens: &Vec<&str>, index: &mut usize, block_type: &str) -> Statement {
-        *index += 1; // Go past 'if' Token
-        let expression: Expression = get_expression_in_round_brackets(tokens, index);
-        let statement: Statement = get_compound_statement(tokens, index);
+    // Functions without '->' do not declare a return type
+    let return_type: DataType = match tokens.next_if_lexeme(&["->"])? {
+        Some(_) => parse_type(tokens)?,
+        None => DataType::None,
+    };
+    return Ok(FnDecl {
+        name: name.lexeme.to_string(),
+        parameters: parameters,
+        return_type: return_type,
+        body: parse_block(tokens)?,
+        doc: None,
+    });
+}
 
-        // If, elif, else
-        let mut typ: StatementType = StatementType::Conditional;
+fn parse_parameter(tokens: &mut TokenStream) -> Result<Parameter, ParseError> {
+    // Parameters are written as 'a: int', or as 'int a' like variable definitions
+    let (name, typ): (Token, DataType) = match tokens.next_if_type(TokenType::DataType)? {
+        Some(datatype) => (
+            tokens.expect(TokenType::Identifier)?,
+            get_datatype_from_str(datatype.lexeme.as_str()),
+        ),
+        None => {
+            let name: Token = tokens.expect(TokenType::Identifier)?;
+            tokens.expect_lexeme(":")?;
+            (name, parse_type(tokens)?)
+        }
+    };
+    let variadic: bool = tokens.next_if_lexeme(&["..."])?.is_some();
+    if variadic && tokens.peek()?.is_some_and(|token| token.lexeme == "=") {
+        return Err(ParseError {
+            message: format!(
+                "Variadic parameter '{}' can not have a default value",
+                name.lexeme
+            ),
+            span: name.span.start..tokens.previous_end(),
+        });
+    }
 
-        // While
-        if block_type == "while" {
-            typ = StatementType::Loop;
+    // Default values bind tighter than assignments so 'b: int = 10' is not one
+    let default: Option<Expr> = match tokens.next_if_lexeme(&["="])? {
+        Some(_) => Some(parse_binary_expression(tokens, 2)?),
+        None => None,
+    };
+    if let Some(default) = &default {
+        if !is_constant_expression(default) {
+            return Err(ParseError {
+                message: format!(
+                    "Default value of parameter '{}' must be a constant expression",
+                    name.lexeme
+                ),
+                span: default.span(),
+            });
         }
+    }
+    return Ok(Parameter {
+        name: name.lexeme.to_string(),
+        typ: typ,
+        default: default,
+        variadic: variadic,
+    });
+}
 
-        Statement {
-            typ: typ,
-            value: Some(block_type.to_string()),
-            options: None,
-            expression: Some(expression),
-            statements: Some(vec![statement]),
-        }
-    }
-
-    fn get_return_statement(tokens: &Vec<&str>, index: &mut usize) -> Statement {
-        *index += 1; // Go past 'return' Token
-        let expression: Expression = get_next_expression(tokens, index);
-
-        // Test if the current Token is ';' and increment index
-        eat_token(";", tokens, index);
-
-        return Statement::new(StatementType::Return, None, None, Some(expression), None);
-    }
-
-    fn eat_token(expected: &str, tokens: &Vec<&str>, index: &mut usize) {
-        // Increment index if the current Token matches with expected
-        let token: &str = tokens
-            .get(*index)
-            .expect("Expected Token but found nothing");
-        if token != expected {
-            panic!("Expected {} but found '{}'", expected, token)
-        }
-        *index += 1;
-    }
-
-    fn get_variable_definition_statement(
-        tokens: &Vec<&str>,
-        index: &mut usize,
-        datatype: DataType,
-    ) -> Statement {
-        // Get variable name from after the DataType Token ('char', 'int', 'str', ...)
-        let variable_name: String = tokens
-            .get(*index + 1)
-            .expect("Could not get Identifier for variable definition")
-            .to_string();
-
-        // Verify if the next Token is '='
-        // Note: Other assignment operators like '+=' and '*=' do not make sense when declaring variable
-        let assignment_token_value: &str = tokens
-            .get(*index + 2)
-            .expect("Expected '=' in variable definition but got nothing");
-        if assignment_token_value != "=" {
-            panic!(
-                "Expected '=' in variable definition but got '{}'",
-                assignment_token_value
-            )
+// Type annotations are DataTypes or arrays of them, e.g. 'int', '[str]' or '[[int; 3]]'
+fn parse_type(tokens: &mut TokenStream) -> Result<DataType, ParseError> {
+    if tokens.next_if_lexeme(&["["])?.is_none() {
+        let datatype: Token = tokens.expect(TokenType::DataType)?;
+        return Ok(get_datatype_from_str(datatype.lexeme.as_str()));
+    }
+    let elem: DataType = parse_type(tokens)?;
+    let size: Option<Box<Expr>> = match tokens.next_if_lexeme(&[";"])? {
+        Some(_) => Some(Box::new(parse_expression(tokens)?)),
+        None => None,
+    };
+    tokens.expect_lexeme("]")?;
+    return Ok(DataType::Array(ArrayType {
+        elem: Box::new(elem),
+        size: size,
+    }));
+}
+
+// Expressions made only of literals and operators, which can be evaluated before running
+fn is_constant_expression(expression: &Expr) -> bool {
+    match &expression.kind {
+        ExprKind::Literal { .. } => true,
+        ExprKind::Unary { operand, .. } | ExprKind::Grouping(operand) => {
+            is_constant_expression(operand)
         }
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } => {
+            !ASSIGNMENT_OPERATORS.contains(&operator.as_str())
+                && is_constant_expression(left)
+                && is_constant_expression(right)
+        }
+        ExprKind::ArrayLit(elements) => elements.iter().all(is_constant_expression),
+        _ => false,
+    }
+}
+
+// Conditional statements consist of 'if' and any 'elif' and 'else' branches following it
+fn parse_conditional(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    tokens.expect_lexeme("if")?;
+    let condition: Expr = parse_expression(tokens)?;
+    let body: Vec<Stmt> = parse_block(tokens)?;
 
-        // Go past the DataType, variable name Identifier and assignment Tokens
-        *index += 3;
-        let expression: Expression = get_next_expression(tokens, index);
+    // Branch bodies are always braced so every 'else' belongs to the chain before it
+    let mut elifs: Vec<(Expr, Vec<Stmt>)> = vec![];
+    while tokens.next_if_lexeme(&["elif"])?.is_some() {
+        elifs.push((parse_expression(tokens)?, parse_block(tokens)?));
+    }
+    let else_body: Option<Vec<Stmt>> = match tokens.next_if_lexeme(&["else"])? {
+        Some(_) => Some(parse_block(tokens)?),
+        None => None,
+    };
+    return Ok(StmtKind::If {
+        condition: condition,
+        body: body,
+        elifs: elifs,
+        else_body: else_body,
+    });
+}
+
+fn parse_loop(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    tokens.expect_lexeme("while")?;
+    return Ok(StmtKind::While {
+        condition: parse_expression(tokens)?,
+        body: parse_block(tokens)?,
+    });
+}
 
-        // Test if the current Token is ';' and increment index
-        eat_token(";", tokens, index);
+fn parse_for(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    // Example loops:
+    // for (let i = 0; i < n; i++) { print(i); }
+    // for x in 0..10 { print(x); }
+    tokens.expect_lexeme("for")?;
+    if tokens.next_if_lexeme(&["("])?.is_none() {
+        let variable: Token = tokens.expect(TokenType::Identifier)?;
+        // 'in' is only a keyword here so it is still a valid name elsewhere
+        tokens.expect_lexeme("in")?;
+        return Ok(StmtKind::ForIn {
+            variable: variable.lexeme.to_string(),
+            iterable: parse_expression(tokens)?,
+            body: parse_block(tokens)?,
+        });
+    }
 
-        Statement {
-            typ: StatementType::Variable(datatype),
-            value: Some(variable_name),
-            options: None,
-            expression: Some(expression),
-            statements: None,
+    // Initializer is a declaration or an Expression ending in ';' like other Statements
+    if tokens.peek()?.is_none() {
+        let end: usize = tokens.end();
+        return Err(ParseError {
+            message: "Expected declaration or Expression to initialize 'for'".to_string(),
+            span: end..end,
+        });
+    }
+    let init: Option<Box<Stmt>> = match tokens.next_if_lexeme(&[";"])? {
+        Some(_) => None,
+        None => {
+            let init: Stmt = parse_statement(tokens)?;
+            if !matches!(init.kind, StmtKind::VarDecl(_) | StmtKind::Expression(_)) {
+                return Err(ParseError {
+                    message: "Expected declaration or Expression to initialize 'for'".to_string(),
+                    span: init.span(),
+                });
+            }
+            Some(Box::new(init))
         }
+    };
+    let condition: Option<Expr> = match tokens.next_if_lexeme(&[";"])? {
+        Some(_) => None,
+        None => {
+            let condition: Expr = parse_expression(tokens)?;
+            tokens.expect_lexeme(";")?;
+            Some(condition)
+        }
+    };
+    let step: Option<Expr> = match tokens.next_if_lexeme(&[")"])? {
+        Some(_) => None,
+        None => {
+            let step: Expr = parse_expression(tokens)?;
+            tokens.expect_lexeme(")")?;
+            Some(step)
+        }
+    };
+    return Ok(StmtKind::For {
+        init: init,
+        condition: condition,
+        step: step,
+        body: parse_block(tokens)?,
+    });
+}
+
+// 'break' and 'continue' are parsed anywhere, checking they are inside a loop is left for later
+fn parse_loop_control(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    let keyword: Token = tokens.expect(TokenType::Keyword)?;
+    tokens.expect_lexeme(";")?;
+    match keyword.lexeme.as_str() {
+        "break" => Ok(StmtKind::Break),
+        _ => Ok(StmtKind::Continue),
     }
+}
 
-    fn get_expression_statement(tokens: &Vec<&str>, index: &mut usize) -> Statement {
-        let expression: Expression = get_next_expression(tokens, index);
+fn parse_return(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    tokens.expect_lexeme("return")?;
+    if tokens.next_if_lexeme(&[";"])?.is_some() {
+        return Ok(StmtKind::Return(None));
+    }
+    let expression: Expr = parse_expression(tokens)?;
+    tokens.expect_lexeme(";")?;
+    return Ok(StmtKind::Return(Some(expression)));
+}
 
-        // Test if the current Token is ';' and increment index
-        eat_token(";", tokens, index);
+fn parse_variable_declaration(tokens: &mut TokenStream) -> Result<VarDecl, ParseError> {
+    // Example declarations:
+    // let x: int = 42; let y; const z = 1;
+    let keyword: Token = tokens.expect(TokenType::Keyword)?;
+    let name: Token = tokens.expect(TokenType::Identifier)?;
+    let typ: DataType = match tokens.next_if_lexeme(&[":"])? {
+        Some(_) => parse_type(tokens)?,
+        None => DataType::None,
+    };
+    let value: Option<Expr> = match tokens.next_if_lexeme(&["="])? {
+        Some(_) => Some(parse_expression(tokens)?),
+        None => None,
+    };
 
-        Statement::new(
-            StatementType::Expression,
-            None,
-            None,
-            Some(expression),
-            None,
-        )
+    // Constants can not be assigned later so they need a value right away
+    let mutable: bool = keyword.lexeme != "const";
+    if !mutable && value.is_none() {
+        return Err(ParseError {
+            message: format!("Constant '{}' must be initialized", name.lexeme),
+            span: name.span,
+        });
     }
+    tokens.expect_lexeme(";")?;
+    return Ok(VarDecl {
+        name: name.lexeme.to_string(),
+        typ: typ,
+        value: value,
+        mutable: mutable,
+        doc: None,
+    });
+}
+
+fn parse_variable_definition(tokens: &mut TokenStream) -> Result<VarDecl, ParseError> {
+    // Note: Other assignment operators like '+=' and '*=' do not make sense when declaring variable
+    let datatype: Token = tokens.expect(TokenType::DataType)?;
+    let name: Token = tokens.expect(TokenType::Identifier)?;
+    tokens.expect_lexeme("=")?;
+    let value: Expr = parse_expression(tokens)?;
+    tokens.expect_lexeme(";")?;
+    return Ok(VarDecl {
+        name: name.lexeme.to_string(),
+        typ: get_datatype_from_str(datatype.lexeme.as_str()),
+        value: Some(value),
+        mutable: true,
+        doc: None,
+    });
+}
 
-    fn get_next_expression(tokens: &Vec<&str>, index: &mut usize) -> Expression {
-        let first_token: &str = tokens
-            .get(*index)
-            .expect("There are no Tokens left for Expression");
+fn parse_expression_statement(tokens: &mut TokenStream) -> Result<StmtKind, ParseError> {
+    let expression: Expr = parse_expression(tokens)?;
+    tokens.expect_lexeme(";")?;
+    return Ok(StmtKind::Expression(expression));
+}
+
+// Operators that store their result in the variable on the left side
+pub(crate) const ASSIGNMENT_OPERATORS: [&str; 11] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=",
+];
+
+// Binding power of binary operators keyed by their lexeme, tighter binding ones have more
+pub(crate) static BINARY_PRECEDENCES: phf::Map<&str, (u8, Associativity)> = phf_map! {
+    "=" => (1, Associativity::Right),
+    "+=" => (1, Associativity::Right),
+    "-=" => (1, Associativity::Right),
+    "*=" => (1, Associativity::Right),
+    "/=" => (1, Associativity::Right),
+    "%=" => (1, Associativity::Right),
+    "&=" => (1, Associativity::Right),
+    "|=" => (1, Associativity::Right),
+    "^=" => (1, Associativity::Right),
+    "<<=" => (1, Associativity::Right),
+    ">>=" => (1, Associativity::Right),
+    // Like assignments, so 'a = b ? c : d' assigns the Ternary and 'a ? b : c ? d : e' nests
+    "?" => (1, Associativity::Right),
+    // Right side is only evaluated when the left one is none
+    "??" => (2, Associativity::Right),
+    ".." => (3, Associativity::Left),
+    "..=" => (3, Associativity::Left),
+    "||" => (4, Associativity::Left),
+    "&&" => (5, Associativity::Left),
+    "|" => (6, Associativity::Left),
+    "^" => (7, Associativity::Left),
+    "&" => (8, Associativity::Left),
+    "==" => (9, Associativity::Left),
+    "!=" => (9, Associativity::Left),
+    "<" => (10, Associativity::Left),
+    "<=" => (10, Associativity::Left),
+    ">" => (10, Associativity::Left),
+    ">=" => (10, Associativity::Left),
+    "<<" => (11, Associativity::Left),
+    ">>" => (11, Associativity::Left),
+    "+" => (12, Associativity::Left),
+    "-" => (12, Associativity::Left),
+    "*" => (13, Associativity::Left),
+    "/" => (13, Associativity::Left),
+    "%" => (13, Associativity::Left),
+    "**" => (15, Associativity::Right),
+};
+
+// Prefix operators bind looser than '**', so -2 ** 2 is -(2 ** 2)
+const PREFIX_OPERATORS: [&str; 5] = ["!", "-", "++", "--", "&"];
+const PREFIX_PRECEDENCE: u8 = 14;
+const POSTFIX_OPERATORS: [&str; 2] = ["++", "--"];
+
+// Parse an Expression from the Tokens, leaving the Token after it unconsumed
+pub(crate) fn parse_expression(tokens: &mut TokenStream) -> Result<Expr, ParseError> {
+    return parse_binary_expression(tokens, 0);
+}
 
-        // Unary Expressions
-        // TODO: Parse post-increment and post-decrement expressions
-        if get_token_type(first_token) == &TokenType::UnaryOperator {
-            return get_unary_expression(first_token.to_string(), tokens, index);
+// Pratt parser taking binary operators as long as they bind at least as tight as the minimum
+fn parse_binary_expression(
+    tokens: &mut TokenStream,
+    min_precedence: u8,
+) -> Result<Expr, ParseError> {
+    let mut left: Expr = parse_prefix_expression(tokens)?;
+    while let Some((precedence, associativity)) = peek_binary_precedence(tokens)? {
+        if precedence < min_precedence {
+            break;
         }
-        let lookahead_value: &str = tokens
-            .get(*index + 1)
-            .expect("Lookahead failed in get_next_expression: Program cannot end to an Expression");
+        let operator: Token = tokens.next()?.unwrap();
 
-        // Literal Expressions
-        if EXPRESSION_DELIMITERS.contains(&lookahead_value) {
-            *index += 1;
-            if lookahead_value == "(" {
-                return get_function_call_expression(first_token.to_string(), tokens, index);
-            }
-            return get_literal_expression(&first_token);
+        // a - b - c is (a - b) - c but a = b = c is a = (b = c)
+        let right_precedence: u8 = match associativity {
+            Associativity::Left => precedence + 1,
+            Associativity::Right => precedence,
+        };
+        if operator.lexeme == "?" {
+            left = parse_ternary(tokens, left, right_precedence)?;
+            continue;
         }
+        let right: Expr = parse_binary_expression(tokens, right_precedence)?;
+        left = get_binary_expression(operator.lexeme.to_string(), left, right);
+    }
+    return Ok(left);
+}
+
+// Parse the rest of a Ternary after its '?', any Expression can be between '?' and ':'
+fn parse_ternary(
+    tokens: &mut TokenStream,
+    condition: Expr,
+    else_precedence: u8,
+) -> Result<Expr, ParseError> {
+    let then_value: Expr = parse_expression(tokens)?;
+    tokens.expect_lexeme(":")?;
+    let else_value: Expr = parse_binary_expression(tokens, else_precedence)?;
+    let span: Range<usize> = condition.span().start..else_value.span().end;
+    let kind: ExprKind = ExprKind::Ternary {
+        condition: Box::new(condition),
+        then_value: Box::new(then_value),
+        else_value: Box::new(else_value),
+    };
+    return Ok(Expr::init(kind, span));
+}
 
-        // Define the type of Expression from the next Expression delimiter
-        let mut temp_index: usize = *index;
-        while temp_index < tokens.len() {
-            temp_index += 1;
-            let token_value: &str = tokens
-                .get(temp_index)
-                .expect("Could not parse current Expression's type");
+fn peek_binary_precedence(
+    tokens: &mut TokenStream,
+) -> Result<Option<(u8, Associativity)>, LexError> {
+    let token: Option<&Token> = tokens.peek()?;
+    return Ok(token.and_then(|token| BINARY_PRECEDENCES.get(token.lexeme.as_str()).copied()));
+}
 
-            // TODO: Parse other binary Expressions than Literals as LHS
-            if BINARY_OPERATORS.contains(&token_value) {
-                return get_binary_expression(token_value.to_string(), tokens, index);
-            }
+fn parse_prefix_expression(tokens: &mut TokenStream) -> Result<Expr, ParseError> {
+    let operator: Token = match tokens.next_if_lexeme(&PREFIX_OPERATORS)? {
+        Some(operator) => operator,
+        None => return parse_postfix_expression(tokens),
+    };
+    let operand: Expr = parse_binary_expression(tokens, PREFIX_PRECEDENCE)?;
+    let span: Range<usize> = operator.span.start..operand.span().end;
+    let operand: Box<Expr> = Box::new(operand);
+    let kind: ExprKind = match operator.lexeme.as_str() {
+        "++" => ExprKind::PreInc(operand),
+        "--" => ExprKind::PreDec(operand),
+        _ => ExprKind::Unary {
+            operator: operator.lexeme.to_string(),
+            operand: operand,
+        },
+    };
+    return Ok(Expr::init(kind, span));
+}
 
-            todo!(
-                "Parsing this kind of Expression is not implemented yet: {:?}",
-                token_value
-            );
-        }
-        todo!(
-            "Parsing this kind of Expression is not implemented yet: {:?}",
-            first_token
-        );
+fn parse_postfix_expression(tokens: &mut TokenStream) -> Result<Expr, ParseError> {
+    // Calls, indexing and field access can be chained like 'f(1)(2)', 'm[i][j]' and 'a.b.c'
+    let mut operand: Expr = parse_primary(tokens)?;
+    while let Some(opener) = tokens.next_if_lexeme(&["(", "[", "."])? {
+        let start: usize = operand.span().start;
+        let kind: ExprKind = match opener.lexeme.as_str() {
+            "(" => ExprKind::Call {
+                callee: Box::new(operand),
+                arguments: parse_list(tokens, ")", "argument")?,
+            },
+            "." => ExprKind::FieldAccess {
+                target: Box::new(operand),
+                field: tokens.expect(TokenType::Identifier)?.lexeme.to_string(),
+            },
+            _ => {
+                let index: Expr = parse_expression(tokens)?;
+                tokens.expect_lexeme("]")?;
+                ExprKind::Index {
+                    target: Box::new(operand),
+                    index: Box::new(index),
+                }
+            }
+        };
+        operand = Expr::init(kind, start..tokens.previous_end());
     }
+    let operator: Token = match tokens.next_if_lexeme(&POSTFIX_OPERATORS)? {
+        Some(operator) => operator,
+        None => return Ok(operand),
+    };
 
-    fn get_unary_expression(operator: String, tokens: &Vec<&str>, index: &mut usize) -> Expression {
-        *index += 1; // Go past unary operator
-        Expression {
-            value: Some(operator),
-            typ: ExpressionType::Unary,
-            expressions: Some(vec![get_next_expression(tokens, index)]),
-        }
+    // 'a++--' would update the result of 'a++' which is not a variable
+    if let Some(token) = tokens.next_if_lexeme(&POSTFIX_OPERATORS)? {
+        return Err(ParseError {
+            message: format!(
+                "Expected only one postfix operator but found '{}'",
+                token.lexeme
+            ),
+            span: token.span,
+        });
     }
+    let span: Range<usize> = operand.span().start..operator.span.end;
+    let kind: ExprKind = match operator.lexeme.as_str() {
+        "++" => ExprKind::PostInc(Box::new(operand)),
+        _ => ExprKind::PostDec(Box::new(operand)),
+    };
+    return Ok(Expr::init(kind, span));
+}
 
-    fn get_binary_expression(
-        operator: String,
-        tokens: &Vec<&str>,
-        index: &mut usize,
-    ) -> Expression {
-        // TODO: Parse other LHS Expressions than Literals
-        let left: Expression = get_literal_expression(tokens.get(*index).unwrap());
-        *index += 2; // Go past binary operator
-        let right: Expression = get_next_expression(tokens, index);
-        Expression {
-            typ: ExpressionType::Binary,
-            value: Some(operator),
-            expressions: Some(vec![left, right]),
+// Parse comma separated Expressions up to and including the closing bracket
+fn parse_list(tokens: &mut TokenStream, closer: &str, item: &str) -> Result<Vec<Expr>, ParseError> {
+    let mut items: Vec<Expr> = vec![];
+    while tokens.next_if_lexeme(&[closer])?.is_none() {
+        if !items.is_empty() {
+            let comma: Token = tokens.expect_lexeme(",")?;
+            if tokens.peek()?.is_some_and(|token| token.lexeme == closer) {
+                return Err(ParseError {
+                    message: format!("Trailing comma is not allowed after the last {}", item),
+                    span: comma.span,
+                });
+            }
         }
+        items.push(parse_expression(tokens)?);
     }
+    return Ok(items);
+}
 
-    fn get_literal_expression(token: &str) -> Expression {
-        if get_token_type(token) == &TokenType::Literal(DataType::Character) {
-            return get_character_literal_expression(token.to_string());
+fn parse_primary(tokens: &mut TokenStream) -> Result<Expr, ParseError> {
+    // Invalid Tokens are left unconsumed so error recovery can synchronize on them
+    let end: usize = tokens.end();
+    let token: Token = match tokens.peek()? {
+        Some(token) if starts_primary(token) => tokens.next()?.unwrap(),
+        Some(token) => {
+            return Err(ParseError {
+                message: format!("Expected Expression but found '{}'", token.lexeme),
+                span: token.span.clone(),
+            })
+        }
+        None => {
+            return Err(ParseError {
+                message: "Expected Expression but found nothing".to_string(),
+                span: end..end,
+            })
+        }
+    };
+    let kind: ExprKind = match token.token_type {
+        TokenType::Literal(typ) => ExprKind::Literal {
+            typ: typ,
+            value: token.value.unwrap_or(token.lexeme.to_string()),
+        },
+        TokenType::Bool => ExprKind::Literal {
+            typ: DataType::Boolean,
+            value: token.lexeme.to_string(),
+        },
+        TokenType::Identifier if starts_struct_literal(tokens)? => {
+            parse_struct_literal(tokens, token.lexeme.to_string())?
         }
-        if get_token_type(token) == &TokenType::Literal(DataType::Integer) {
-            return get_integer_literal_expression(token.to_string());
+        // 'none' is the only literal without a DataType
+        TokenType::Null => ExprKind::Literal {
+            typ: DataType::None,
+            value: token.lexeme.to_string(),
+        },
+        TokenType::Identifier if tokens.next_if_lexeme(&["::"])?.is_some() => {
+            ExprKind::EnumVariant {
+                name: token.lexeme.to_string(),
+                variant: tokens.expect(TokenType::Identifier)?.lexeme.to_string(),
+            }
         }
-        if get_token_type(token) == &TokenType::Literal(DataType::String) {
-            return get_string_literal_expression(token.to_string());
+        TokenType::Identifier => ExprKind::Identifier(token.lexeme.to_string()),
+        TokenType::Delimiter if token.lexeme == "(" => return parse_grouping(tokens, token),
+        TokenType::Delimiter if token.lexeme == "[" => {
+            ExprKind::ArrayLit(parse_list(tokens, "]", "element")?)
         }
-        // Identifier
-        return get_identifier_literal_expression(token.to_string());
-    }
+        TokenType::BinaryOperator => parse_lambda(tokens, &token)?,
+        TokenType::Keyword => parse_match(tokens)?,
+        _ => unreachable!("starts_primary accepted '{}'", token.lexeme),
+    };
+    return Ok(Expr::init(kind, token.span.start..tokens.previous_end()));
+}
 
-    fn get_function_call_expression(
-        function_name: String,
-        tokens: &Vec<&str>,
-        index: &mut usize,
-    ) -> Expression {
-        *index += 1; // Go past opening round bracket of function call
-        let mut token_value: &str = tokens
-            .get(*index)
-            .expect("Expected function parameter but got nothing");
+fn starts_primary(token: &Token) -> bool {
+    match token.token_type {
+        TokenType::Literal(_) | TokenType::Bool | TokenType::Identifier | TokenType::Null => true,
+        TokenType::Delimiter => token.lexeme == "(" || token.lexeme == "[",
+        // Lambdas start with their parameters, '||' when there are none
+        TokenType::BinaryOperator => token.lexeme == "|" || token.lexeme == "||",
+        TokenType::Keyword => token.lexeme == "match",
+        _ => false,
+    }
+}
 
-        // Parse all parameter Expressions for the function
-        let mut parameter_expressions: Vec<Expression> = vec![];
-        while *index < tokens.len() && token_value != ")" {
-            parameter_expressions.push(get_next_expression(tokens, index));
+// Only 'Name { field:' starts a struct literal so 'if a { b; }' keeps its body
+fn starts_struct_literal(tokens: &mut TokenStream) -> Result<bool, LexError> {
+    let opens: bool = tokens.peek_nth(0)?.is_some_and(|token| token.lexeme == "{");
+    let named: bool = opens
+        && tokens
+            .peek_nth(1)?
+            .is_some_and(|token| token.token_type == TokenType::Identifier);
+    return Ok(named && tokens.peek_nth(2)?.is_some_and(|token| token.lexeme == ":"));
+}
 
-            // Verify if the current Token is ',' or ')'
-            token_value = tokens
-                .get(*index)
-                .expect("Expected function parameter but got nothing");
-            if token_value == ")" {
-                break;
-            }
-            *index += 1;
+// Parse the rest of a struct literal after its name
+fn parse_struct_literal(tokens: &mut TokenStream, name: String) -> Result<ExprKind, ParseError> {
+    tokens.expect_lexeme("{")?;
+    let mut fields: Vec<(String, Expr)> = vec![];
+    while tokens.next_if_lexeme(&["}"])?.is_none() {
+        if !fields.is_empty() {
+            tokens.expect_lexeme(",")?;
         }
+        let field: Token = tokens.expect(TokenType::Identifier)?;
+        tokens.expect_lexeme(":")?;
+        fields.push((field.lexeme.to_string(), parse_expression(tokens)?));
+    }
+    return Ok(ExprKind::StructLit {
+        name: name,
+        fields: fields,
+    });
+}
 
-        *index += 1; // Go past ')' Token
-        Expression {
-            typ: ExpressionType::FunctionCall,
-            value: Some(function_name),
-            expressions: Some(parameter_expressions),
+// Parse the rest of a Match after its keyword
+fn parse_match(tokens: &mut TokenStream) -> Result<ExprKind, ParseError> {
+    // Example match:
+    // match x { 1 => a, 2 => { b; }, _ => c }
+    let scrutinee: Expr = parse_expression(tokens)?;
+    tokens.expect_lexeme("{")?;
+    let mut arms: Vec<MatchArm> = vec![];
+    while tokens.next_if_lexeme(&["}"])?.is_none() {
+        if !arms.is_empty() {
+            tokens.expect_lexeme(",")?;
         }
+        // Patterns are parsed like operands so '-1' and 'Color::Red' can be matched
+        let pattern: Pattern = match tokens.next_if_lexeme(&["_"])? {
+            Some(_) => Pattern::Wildcard,
+            None => Pattern::Value(parse_binary_expression(tokens, PREFIX_PRECEDENCE)?),
+        };
+        tokens.expect_lexeme("=>")?;
+        let body: ArmBody = match tokens.peek()? {
+            Some(token) if token.lexeme == "{" => ArmBody::Block(parse_block(tokens)?),
+            _ => ArmBody::Expression(parse_expression(tokens)?),
+        };
+        arms.push(MatchArm {
+            pattern: pattern,
+            body: body,
+        });
     }
+    return Ok(ExprKind::Match {
+        scrutinee: Box::new(scrutinee),
+        arms: arms,
+    });
+}
+
+// Parse the rest of a Lambda after its opening '|' or '||'
+fn parse_lambda(tokens: &mut TokenStream, opener: &Token) -> Result<ExprKind, ParseError> {
+    // Example lambdas:
+    // |a: int, b: int| -> int { a + b }
+    // |x| x + 1
+    let mut parameters: Vec<Parameter> = vec![];
+    if opener.lexeme == "|" {
+        while tokens.next_if_lexeme(&["|"])?.is_none() {
+            if !parameters.is_empty() {
+                tokens.expect_lexeme(",")?;
+            }
+            let name: Token = tokens.expect(TokenType::Identifier)?;
 
-    fn get_character_literal_expression(token_value: String) -> Expression {
-        Expression {
-            value: Some(token_value),
-            typ: ExpressionType::Literal(DataType::Character),
-            expressions: None,
+            // Parameters without a type annotation take the type of the argument
+            let typ: DataType = match tokens.next_if_lexeme(&[":"])? {
+                Some(_) => parse_type(tokens)?,
+                None => DataType::None,
+            };
+            parameters.push(Parameter {
+                name: name.lexeme.to_string(),
+                typ: typ,
+                default: None,
+                variadic: false,
+            });
         }
     }
 
-    fn get_integer_literal_expression(token_value: String) -> Expression {
-        Expression {
-            value: Some(token_value),
-            typ: ExpressionType::Literal(DataType::Integer),
-            expressions: None,
+    let return_type: DataType = match tokens.next_if_lexeme(&["->"])? {
+        Some(_) => parse_type(tokens)?,
+        None => DataType::None,
+    };
+    let body: LambdaBody = match tokens.peek()? {
+        Some(token) if token.lexeme == "{" => LambdaBody::Block(parse_block_with(tokens, true)?),
+        _ => LambdaBody::Expression(Box::new(parse_expression(tokens)?)),
+    };
+    return Ok(ExprKind::Lambda {
+        parameters: parameters,
+        return_type: return_type,
+        body: body,
+    });
+}
+
+// Parse the rest of a Grouping after its opening bracket
+fn parse_grouping(tokens: &mut TokenStream, opener: Token) -> Result<Expr, ParseError> {
+    if let Some(closer) = tokens.next_if_lexeme(&[")"])? {
+        return Err(ParseError {
+            message: "Expected Expression inside '()'".to_string(),
+            span: opener.span.start..closer.span.end,
+        });
+    }
+    let expression: Expr = parse_expression(tokens)?;
+
+    // Mismatched brackets point back to the opener
+    let found: String = match tokens.peek()? {
+        Some(token) if token.lexeme == ")" => {
+            tokens.next()?;
+            let span: Range<usize> = opener.span.start..tokens.previous_end();
+            return Ok(Expr::init(ExprKind::Grouping(Box::new(expression)), span));
         }
+        Some(token) => format!("'{}'", token.lexeme),
+        None => "nothing".to_string(),
+    };
+    return Err(ParseError {
+        message: format!("Expected ')' to close this '(' but found {}", found),
+        span: opener.span,
+    });
+}
+
+fn get_binary_expression(operator: String, left: Expr, right: Expr) -> Expr {
+    let span: Range<usize> = left.span().start..right.span().end;
+    let kind: ExprKind = ExprKind::Binary {
+        operator: operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    };
+    Expr::init(kind, span)
+}
+
+// Passes over the AST implement the nodes they care about and walk into the rest, e.g. a
+// visit_expr handling only calls ends with walk_expr(self, expression) for other nodes
+#[allow(dead_code)]
+pub(crate) trait Visitor {
+    fn visit_stmt(&mut self, statement: &Stmt) {
+        walk_stmt(self, statement);
+    }
+
+    fn visit_expr(&mut self, expression: &Expr) {
+        walk_expr(self, expression);
     }
+}
+
+#[allow(dead_code)]
+pub(crate) fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    walk_block(visitor, &program.statements);
+}
 
-    fn get_string_literal_expression(token_value: String) -> Expression {
-        Expression {
-            value: Some(token_value),
-            typ: ExpressionType::Literal(DataType::String),
-            expressions: None,
+// Visit the direct children of the Statement
+pub(crate) fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, statement: &Stmt) {
+    match &statement.kind {
+        StmtKind::Block(body) => walk_block(visitor, body),
+        StmtKind::Break | StmtKind::Continue | StmtKind::NoOperation => {}
+        StmtKind::EnumDecl(_) | StmtKind::StructDecl(_) => {}
+        StmtKind::Expression(expression) => visitor.visit_expr(expression),
+        StmtKind::FnDecl(function) => walk_block(visitor, &function.body),
+        StmtKind::If {
+            condition,
+            body,
+            elifs,
+            else_body,
+        } => {
+            visitor.visit_expr(condition);
+            walk_block(visitor, body);
+            for (condition, body) in elifs.iter() {
+                visitor.visit_expr(condition);
+                walk_block(visitor, body);
+            }
+            if let Some(body) = else_body {
+                walk_block(visitor, body);
+            }
+        }
+        StmtKind::For {
+            init,
+            condition,
+            step,
+            body,
+        } => {
+            if let Some(init) = init {
+                visitor.visit_stmt(init);
+            }
+            condition
+                .iter()
+                .for_each(|condition| visitor.visit_expr(condition));
+            step.iter().for_each(|step| visitor.visit_expr(step));
+            walk_block(visitor, body);
+        }
+        StmtKind::ForIn { iterable, body, .. } => {
+            visitor.visit_expr(iterable);
+            walk_block(visitor, body);
+        }
+        StmtKind::Return(value) => value.iter().for_each(|value| visitor.visit_expr(value)),
+        StmtKind::VarDecl(declaration) => {
+            if let Some(value) = &declaration.value {
+                visitor.visit_expr(value);
+            }
+        }
+        StmtKind::While { condition, body } => {
+            visitor.visit_expr(condition);
+            walk_block(visitor, body);
         }
     }
+}
 
-    fn get_identifier_literal_expression(token_value: String) -> Expression {
-        Expression {
-            value: Some(token_value),
-            typ: ExpressionType::Identifier,
-            expressions: None,
+// Visit the direct children of the Expression
+pub(crate) fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expr) {
+    match &expression.kind {
+        ExprKind::Literal { .. } | ExprKind::Identifier(_) | ExprKind::EnumVariant { .. } => {}
+        ExprKind::Unary { operand, .. }
+        | ExprKind::PreInc(operand)
+        | ExprKind::PreDec(operand)
+        | ExprKind::PostInc(operand)
+        | ExprKind::PostDec(operand)
+        | ExprKind::Grouping(operand) => visitor.visit_expr(operand),
+        ExprKind::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
         }
+        ExprKind::Ternary {
+            condition,
+            then_value,
+            else_value,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(then_value);
+            visitor.visit_expr(else_value);
+        }
+        ExprKind::Call { callee, arguments } => {
+            visitor.visit_expr(callee);
+            arguments
+                .iter()
+                .for_each(|argument| visitor.visit_expr(argument));
+        }
+        ExprKind::ArrayLit(elements) => elements
+            .iter()
+            .for_each(|element| visitor.visit_expr(element)),
+        ExprKind::Index { target, index } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(index);
+        }
+        ExprKind::StructLit { fields, .. } => fields
+            .iter()
+            .for_each(|(_, value)| visitor.visit_expr(value)),
+        ExprKind::FieldAccess { target, .. } => visitor.visit_expr(target),
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms.iter() {
+                if let Pattern::Value(value) = &arm.pattern {
+                    visitor.visit_expr(value);
+                }
+                match &arm.body {
+                    ArmBody::Block(body) => walk_block(visitor, body),
+                    ArmBody::Expression(expression) => visitor.visit_expr(expression),
+                }
+            }
+        }
+        ExprKind::Lambda { body, .. } => match body {
+            LambdaBody::Block(body) => walk_block(visitor, body),
+            LambdaBody::Expression(expression) => visitor.visit_expr(expression),
+        },
     }
 }
 
+fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, body: &[Stmt]) {
+    body.iter()
+        .for_each(|statement| visitor.visit_stmt(statement));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::Parser;
+
+    fn parse_expression_from(code: &str) -> Result<Expr, ParseError> {
+        let mut parser: Parser = Parser::init(code);
+        let mut tokens: TokenStream = TokenStream::init(parser.tokens());
+        return parse_expression(&mut tokens);
+    }
+
+    // Nodes in expected trees have no spans as those are not compared
+    fn expr(kind: ExprKind) -> Expr {
+        Expr::init(kind, 0..0)
+    }
+
+    fn stmt(kind: StmtKind) -> Stmt {
+        Stmt::init(kind, 0..0)
+    }
+
+    fn get_integer(value: &str) -> Expr {
+        expr(ExprKind::Literal {
+            typ: DataType::Integer,
+            value: value.to_string(),
+        })
+    }
+
+    fn get_identifier(name: &str) -> Expr {
+        expr(ExprKind::Identifier(name.to_string()))
+    }
 
     #[test]
-    fn test_no_operation_statement_ast() {
-        let tokens: Vec<&str> = vec![";", ";"];
-        let program: Program = generate_ast(&tokens);
+    fn test_expression_precedence_ast() {
         assert_eq!(
-            program,
-            Program {
-                statements: vec![
-                    Statement::new(StatementType::NoOperation, None, None, None, None),
-                    Statement::new(StatementType::NoOperation, None, None, None, None),
-                ]
-            }
-        )
+            parse_expression_from("1 + 2 * 3").unwrap(),
+            get_binary_expression(
+                "+".to_string(),
+                get_integer("1"),
+                get_binary_expression("*".to_string(), get_integer("2"), get_integer("3")),
+            )
+        );
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("1 * 2 + 3", "((1 * 2) + 3)"),
+            ("(1 + 2) * 3", "(((1 + 2)) * 3)"),
+            ("a - b - c", "((a - b) - c)"),
+            ("a < b + 1 == c", "((a < (b + 1)) == c)"),
+            ("a || b && !c", "(a || (b && (!c)))"),
+            ("a | b ^ c & d", "(a | (b ^ (c & d)))"),
+            ("1 << 2 + 3", "(1 << (2 + 3))"),
+            ("-a * b % c", "(((-a) * b) % c)"),
+            ("0..n + 1", "(0 .. (n + 1))"),
+            ("-2 ** 2", "(-(2 ** 2))"),
+            ("'c' + \"s\" + 1.5 + true", "((('c' + \"s\") + 1.5) + true)"),
+        ];
+        for (code, nesting) in expressions {
+            assert_eq!(parse_expression_from(code).unwrap().to_string(), nesting);
+        }
     }
 
     #[test]
-    fn test_return_statement_ast() {
-        let variable_name: &str = "var_name";
-        let tokens: Vec<&str> = vec!["return", variable_name, ";"];
-        let program: Program = generate_ast(&tokens);
+    fn test_exponent_precedence_ast() {
         assert_eq!(
-            program,
-            Program {
-                statements: vec![Statement::new(
-                    StatementType::Return,
-                    None,
-                    None,
-                    Some(Expression::new(
-                        ExpressionType::Identifier,
-                        Some(variable_name.to_string()),
-                        None
-                    )),
-                    None
-                ),]
-            }
-        )
+            parse_expression_from("2 * 3 ** 2").unwrap(),
+            get_binary_expression(
+                "*".to_string(),
+                get_integer("2"),
+                get_binary_expression("**".to_string(), get_integer("3"), get_integer("2")),
+            )
+        );
+        assert_eq!(
+            parse_expression_from("2 ** 3 ** 2").unwrap(),
+            get_binary_expression(
+                "**".to_string(),
+                get_integer("2"),
+                get_binary_expression("**".to_string(), get_integer("3"), get_integer("2")),
+            )
+        );
+        assert_eq!(
+            parse_expression_from("2 ** 3 * 4").unwrap().to_string(),
+            "((2 ** 3) * 4)"
+        );
     }
 
     #[test]
-    fn test_nested_compound_statement_ast() {
-        let tokens: Vec<&str> = vec!["{", "{", "}", ";", "}"];
-        let program: Program = generate_ast(&tokens);
-        assert_eq!(
-            program,
-            Program {
-                statements: vec![Statement::new(
-                    StatementType::Compound,
-                    None,
-                    None,
-                    None,
-                    Some(vec![
-                        Statement::new(StatementType::Compound, None, None, None, Some(vec![])),
-                        Statement::new(StatementType::NoOperation, None, None, None, None),
-                    ])
-                ),] // statements
-            }
-        )
+    fn test_binary_precedences() {
+        // Every BinaryOperator needs a precedence to be parsed
+        for operator in crate::defs::BINARY_OPERATORS {
+            assert!(BINARY_PRECEDENCES.contains_key(operator), "{}", operator);
+        }
     }
 
     #[test]
-    fn test_unary_expression_statement_ast() {
-        let unary_operator: &str = "++";
-        let variable_name: &str = "var_name";
-        let tokens: Vec<&str> = vec![unary_operator, variable_name, ";"];
-        let program: Program = generate_ast(&tokens);
+    fn test_ternary_ast() {
         assert_eq!(
-            program,
-            Program {
-                statements: vec![Statement::new(
-                    StatementType::Expression,
-                    None,
-                    None,
-                    Some(Expression::new(
-                        ExpressionType::Unary,
-                        Some(unary_operator.to_string()),
-                        Some(vec![Expression::new(
-                            ExpressionType::Identifier,
-                            Some(variable_name.to_string()),
-                            None
-                        ),])
-                    )),
-                    None
-                ),] // statements
-            }
-        )
+            parse_expression_from("a ? b : c"),
+            Ok(expr(ExprKind::Ternary {
+                condition: Box::new(get_identifier("a")),
+                then_value: Box::new(get_identifier("b")),
+                else_value: Box::new(get_identifier("c")),
+            }))
+        );
+
+        // Ternaries nest to the right and bind tighter than the assignment around them
+        let expressions: Vec<(&str, &str)> = vec![
+            ("a ? b : c ? d : e", "(a ? b : (c ? d : e))"),
+            ("a ? b ? c : d : e", "(a ? (b ? c : d) : e)"),
+            ("x = a ? b : c", "(x = (a ? b : c))"),
+            ("a || b ? c + 1 : d * 2", "((a || b) ? (c + 1) : (d * 2))"),
+            ("a ? x = 1 : y", "(a ? (x = 1) : y)"),
+            ("a ? b : x = 1", "(a ? b : (x = 1))"),
+        ];
+        for (code, expression) in expressions {
+            assert_eq!(
+                parse_expression_from(code).unwrap().to_string(),
+                expression,
+                "{}",
+                code
+            );
+        }
+
+        assert!(parse_expression_from("a ? b").is_err());
+        assert!(parse_expression_from("a ? : c").is_err());
     }
 
     #[test]
-    fn test_binary_expression_statement_ast() {
-        // TODO: Test correct operator precedence
-        // a + b * c
-        let tokens: Vec<&str> = vec!["a", "+", "b", "*", "c", ";"];
-        let program: Program = generate_ast(&tokens);
+    fn test_null_coalescing_ast() {
         assert_eq!(
-            program,
-            Program {
-                statements: vec![Statement::new(
-                    StatementType::Expression,
-                    None,
-                    None,
-                    Some(Expression::new(
-                        ExpressionType::Binary,
-                        Some("+".to_string()),
-                        Some(vec![
-                            Expression::new(
-                                ExpressionType::Identifier,
-                                Some("a".to_string()),
-                                None
-                            ),
-                            Expression::new(
-                                ExpressionType::Binary,
-                                Some("*".to_string()),
-                                Some(vec![
-                                    Expression::new(
-                                        ExpressionType::Identifier,
-                                        Some("b".to_string()),
-                                        None
-                                    ),
-                                    Expression::new(
-                                        ExpressionType::Identifier,
-                                        Some("c".to_string()),
-                                        None
-                                    ),
-                                ])
-                            ),
-                        ])
-                    )),
-                    None
-                ),] // statements
-            }
-        )
+            parse_expression_from("a ?? none"),
+            Ok(get_binary_expression(
+                "??".to_string(),
+                get_identifier("a"),
+                expr(ExprKind::Literal {
+                    typ: DataType::None,
+                    value: "none".to_string(),
+                })
+            ))
+        );
+
+        // Looser than every other binary operator but tighter than Ternaries and assignments
+        let expressions: Vec<(&str, &str)> = vec![
+            ("a ?? b ?? c", "(a ?? (b ?? c))"),
+            ("a || b ?? c && d", "((a || b) ?? (c && d))"),
+            ("x = a ?? b", "(x = (a ?? b))"),
+            ("a ?? b ? c : d", "((a ?? b) ? c : d)"),
+        ];
+        for (code, expression) in expressions {
+            assert_eq!(
+                parse_expression_from(code).unwrap().to_string(),
+                expression,
+                "{}",
+                code
+            );
+        }
+        assert!(parse_expression_from("a ??").is_err());
     }
 
     #[test]
-    fn test_variable_assignment_statement_ast() {
-        let variable_name: &str = "var_name";
-        let tokens: Vec<&str> = vec!["int", variable_name, "=", "a", "*", "b", ";"];
-        let program: Program = generate_ast(&tokens);
+    fn test_assignment_associativity_ast() {
         assert_eq!(
-            program,
-            Program {
-                statements: vec![Statement {
-                    typ: StatementType::Variable(DataType::Integer),
-                    value: Some(variable_name.to_string()),
-                    options: None,
-                    expression: Some(Expression::new(
-                        ExpressionType::Binary,
-                        Some("*".to_string()),
-                        Some(vec![
-                            Expression::new(
-                                ExpressionType::Identifier,
-                                Some("a".to_string()),
-                                None
-                            ),
-                            Expression::new(
-                                ExpressionType::Identifier,
-                                Some("b".to_string()),
-                                None
-                            ),
-                        ])
-                    )), // expression
-                    statements: None
-                },] // statements
-            }
-        )
+            parse_expression_from("a = b = c").unwrap(),
+            get_binary_expression(
+                "=".to_string(),
+                get_identifier("a"),
+                get_binary_expression("=".to_string(), get_identifier("b"), get_identifier("c")),
+            )
+        );
+        assert_eq!(
+            parse_expression_from("a += b * 2").unwrap().to_string(),
+            "(a += (b * 2))"
+        );
     }
 
     #[test]
-    fn test_conditional_statement_ast() {
-        // if(var_name) { return var_name; }
-        let keyword: &str = "if";
-        let variable_name: &str = "var_name";
-        let tokens: Vec<&str> = vec![
-            keyword,
-            "(",
-            variable_name,
-            ")",
-            "{",
-            "return",
-            variable_name,
-            ";",
-            "}",
+    fn test_invalid_expression_ast() {
+        assert_eq!(
+            parse_expression_from("1 + * 2"),
+            Err(ParseError {
+                message: "Expected Expression but found '*'".to_string(),
+                span: 4..5,
+            })
+        );
+        assert_eq!(parse_expression_from("1 +").unwrap_err().span, 3..3);
+    }
+
+    #[test]
+    fn test_grouping_expression_ast() {
+        let expression: Expr = parse_expression_from("(a + b) * c").unwrap();
+        assert_eq!(
+            expression,
+            get_binary_expression(
+                "*".to_string(),
+                expr(ExprKind::Grouping(Box::new(get_binary_expression(
+                    "+".to_string(),
+                    get_identifier("a"),
+                    get_identifier("b")
+                )))),
+                get_identifier("c"),
+            )
+        );
+        assert_eq!(
+            parse_expression_from("a ** (b ** c) ** ((d))")
+                .unwrap()
+                .to_string(),
+            "(a ** (((b ** c)) ** ((d))))"
+        );
+    }
+
+    #[test]
+    fn test_invalid_grouping_expression_ast() {
+        assert_eq!(
+            parse_expression_from("x * ( )"),
+            Err(ParseError {
+                message: "Expected Expression inside '()'".to_string(),
+                span: 4..7,
+            })
+        );
+        assert_eq!(
+            parse_expression_from("(1 + 2"),
+            Err(ParseError {
+                message: "Expected ')' to close this '(' but found nothing".to_string(),
+                span: 0..1,
+            })
+        );
+        assert_eq!(
+            parse_expression_from("((1 + 2) ]"),
+            Err(ParseError {
+                message: "Expected ')' to close this '(' but found ']'".to_string(),
+                span: 0..1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_call_expression_ast() {
+        let expression: Expr = parse_expression_from("foo()").unwrap();
+        assert_eq!(
+            expression,
+            expr(ExprKind::Call {
+                callee: Box::new(get_identifier("foo")),
+                arguments: vec![],
+            })
+        );
+        let expression: Expr = parse_expression_from("foo(a + 1)").unwrap();
+        assert_eq!(
+            expression,
+            expr(ExprKind::Call {
+                callee: Box::new(get_identifier("foo")),
+                arguments: vec![get_binary_expression(
+                    "+".to_string(),
+                    get_identifier("a"),
+                    get_integer("1")
+                )],
+            })
+        );
+
+        // Calls bind tighter than any operator
+        let expressions: Vec<(&str, &str)> = vec![
+            ("foo(1, 2, bar())", "foo(1, 2, bar())"),
+            ("foo(bar(baz(x)))", "foo(bar(baz(x)))"),
+            ("-f(x) * 2", "((-f(x)) * 2)"),
+            ("f(1)(2)", "f(1)(2)"),
+            ("f()++", "(f()++)"),
         ];
-        let program: Program = generate_ast(&tokens);
+        for (code, nested) in expressions {
+            assert_eq!(parse_expression_from(code).unwrap().to_string(), nested);
+        }
+    }
+
+    #[test]
+    fn test_invalid_call_expression_ast() {
+        assert_eq!(
+            parse_expression_from("foo(1,)"),
+            Err(ParseError {
+                message: "Trailing comma is not allowed after the last argument".to_string(),
+                span: 5..6,
+            })
+        );
+        assert_eq!(
+            parse_expression_from("foo(1 2)").unwrap_err().message,
+            "Expected ',' but found '2'"
+        );
+        assert_eq!(
+            parse_expression_from("foo(,)").unwrap_err().message,
+            "Expected Expression but found ','"
+        );
+        assert_eq!(
+            parse_expression_from("foo(1").unwrap_err().message,
+            "Expected ',' but found nothing"
+        );
+    }
+
+    #[test]
+    fn test_array_expression_ast() {
+        let expression: Expr = parse_expression_from("[1, 2, 3]").unwrap();
+        assert_eq!(
+            expression,
+            expr(ExprKind::ArrayLit(vec![
+                get_integer("1"),
+                get_integer("2"),
+                get_integer("3")
+            ]))
+        );
+        assert_eq!(
+            parse_expression_from("[]").unwrap(),
+            expr(ExprKind::ArrayLit(vec![]))
+        );
+        assert_eq!(
+            parse_expression_from("[[1], []]").unwrap().to_string(),
+            "[[1], []]"
+        );
+        assert_eq!(
+            parse_expression_from("[1, 2,]").unwrap_err().message,
+            "Trailing comma is not allowed after the last element"
+        );
+    }
+
+    #[test]
+    fn test_index_expression_ast() {
+        let expression: Expr = parse_expression_from("m[i][j]").unwrap();
+        assert_eq!(
+            expression,
+            expr(ExprKind::Index {
+                target: Box::new(expr(ExprKind::Index {
+                    target: Box::new(get_identifier("m")),
+                    index: Box::new(get_identifier("i")),
+                })),
+                index: Box::new(get_identifier("j")),
+            })
+        );
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("a[i + 1] * 2", "(a[(i + 1)] * 2)"),
+            ("f(x)[0]", "f(x)[0]"),
+            ("[1, 2][0]", "[1, 2][0]"),
+            ("a[0](x)", "a[0](x)"),
+        ];
+        for (code, nested) in expressions {
+            assert_eq!(parse_expression_from(code).unwrap().to_string(), nested);
+        }
+        assert_eq!(
+            parse_expression_from("a[]").unwrap_err().message,
+            "Expected Expression but found ']'"
+        );
+        assert_eq!(
+            parse_expression_from("a[1").unwrap_err().message,
+            "Expected ']' but found nothing"
+        );
+    }
+
+    #[test]
+    fn test_expression_span() {
+        let expression: Expr = parse_expression_from("1 + 2").unwrap();
+        assert_eq!(expression.span(), 0..5);
+
+        // Child nodes have their own spans inside the parent's one
+        let expression: Expr = parse_expression_from("-f(a, [b])[0] * (c++)").unwrap();
+        assert_eq!(expression.span(), 0..21);
+        let ExprKind::Binary { left, right, .. } = &expression.kind else {
+            panic!("Expected Binary but got {:?}", expression);
+        };
+        assert_eq!(left.span(), 0..13);
+        assert_eq!(right.span(), 16..21);
+        let ExprKind::Unary { operand, .. } = &left.kind else {
+            panic!("Expected Unary but got {:?}", left);
+        };
+        assert_eq!(operand.span(), 1..13);
+    }
+
+    #[test]
+    fn test_statement_span() {
+        let code: &str = "let x = 1;\nfun main() {\n  return x;\n}";
+        let program: Program = generate_ast_from(code).unwrap();
+        assert_eq!(program.statements[0].span(), 0..10);
+        assert_eq!(program.statements[1].span(), 11..37);
+        let StmtKind::FnDecl(function) = &program.statements[1].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[1]);
+        };
+        assert_eq!(&code[function.body[0].span()], "return x;");
+    }
+
+    fn generate_ast_from(code: &str) -> Result<Program, ParseError> {
+        let mut parser: Parser = Parser::init(code);
+        return generate_ast(&parser.parse()?);
+    }
+
+    fn generate_ast_with_trivia_from(code: &str) -> Program {
+        let mut parser: Parser = Parser::init(code).with_trivia(true);
+        return generate_ast(&parser.parse().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_doc_comment_ast() {
+        let code: &str =
+            "/// Sums two integers\n/// and returns the sum\nfun sum(a: int, b: int) -> int {
+            return a + b;
+        }";
+        let program: Program = generate_ast_with_trivia_from(code);
+        let StmtKind::FnDecl(function) = &program.statements[0].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(
+            function.doc,
+            Some("Sums two integers\nand returns the sum".to_string())
+        );
+        assert_eq!(program.statements[0].span().start, 46);
+
+        // Same Program is parsed with and without trivia, apart from the doc
+        let mut parser: Parser = Parser::init(code);
+        assert_eq!(
+            generate_ast(&parser.parse().unwrap()).unwrap().statements[0].kind,
+            StmtKind::FnDecl(FnDecl {
+                doc: None,
+                ..function.clone()
+            })
+        );
+
+        let code: &str = "/**\n * Largest value\n */\nconst MAX = 10;";
+        let program: Program = generate_ast_with_trivia_from(code);
+        let StmtKind::VarDecl(declaration) = &program.statements[0].kind else {
+            panic!("Expected VarDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(declaration.doc, Some("Largest value".to_string()));
+    }
+
+    #[test]
+    fn test_detached_doc_comment_ast() {
+        // Doc comments need to be right before a declaration
+        let codes: Vec<&str> = vec![
+            "/// Not a declaration\nx = 1; let y = 2;",
+            "/// Detached by a comment\n// TODO\nlet y = 2;",
+            "//// Not a doc comment\nlet y = 2;",
+            "/*** Not a doc comment */ let y = 2;",
+            "let y = /** Inside */ 2;",
+        ];
+        for code in codes {
+            let program: Program = generate_ast_with_trivia_from(code);
+            for statement in program.statements.iter() {
+                if let StmtKind::VarDecl(declaration) = &statement.kind {
+                    assert_eq!(declaration.doc, None, "{}", code);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ast_serde_round_trip() {
+        let code: &str = "fun main(a: int) -> int { let x = [a, -1]; if x[0] { return f(x); } }";
+        let program: Program = generate_ast_from(code).unwrap();
+        let json: String = serde_json::to_string(&program).unwrap();
+        let deserialized: Program = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, program);
+
+        // Spans are not compared with the nodes so check them from the JSON
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+        let json: String = serde_json::to_string(&generate_ast_from("1 + 2;").unwrap()).unwrap();
+        assert!(
+            json.starts_with(r#"{"statements":[{"kind":"Expression","value":{"kind":"Binary","#),
+            "{}",
+            json
+        );
+        assert!(json.ends_with(r#""span":{"start":0,"end":5}},"span":{"start":0,"end":6}}]}"#));
+    }
+
+    #[test]
+    fn test_generating_ast_with_recovery() {
+        let code: &str = "let a = ; let b = 1; return 1 + * 2; c; { d + }";
+        let mut parser: Parser = Parser::init(code);
+        let (program, errors): (Program, Vec<ParseError>) =
+            generate_ast_recover(&parser.parse().unwrap());
+        assert_eq!(
+            errors,
+            vec![
+                ParseError {
+                    message: "Expected Expression but found ';'".to_string(),
+                    span: 8..9,
+                },
+                ParseError {
+                    message: "Expected Expression but found '*'".to_string(),
+                    span: 32..33,
+                },
+                ParseError {
+                    message: "Expected Expression but found '}'".to_string(),
+                    span: 46..47,
+                },
+            ]
+        );
+
+        // Valid statements between the broken ones are still parsed
         assert_eq!(
             program,
             Program {
                 statements: vec![
-                    Statement {
-                        typ: StatementType::Conditional,
-                        value: Some(keyword.to_string()),
-                        options: None,
-                        expression: Some(Expression::new(
-                            ExpressionType::Identifier,
-                            Some(variable_name.to_string()),
-                            None
-                        )),
-                        statements: Some(vec![Statement {
-                            typ: StatementType::Compound,
-                            value: None,
-                            options: None,
-                            expression: None,
-                            statements: Some(vec![
-                                Statement {
-                                    typ: StatementType::Return,
-                                    value: None,
-                                    options: None,
-                                    expression: Some(Expression {
-                                        typ: ExpressionType::Identifier,
-                                        value: Some(variable_name.to_string()),
-                                        expressions: None,
-                                    }),
-                                    statements: None,
-                                } // Statement (inner)
-                            ]) // Statement.statements (inner)
-                        }]), // Statement.statements (outer)
-                    } // Statement (outer)
-                ] // Program.statements
+                    stmt(StmtKind::VarDecl(VarDecl {
+                        name: "b".to_string(),
+                        typ: DataType::None,
+                        value: Some(get_integer("1")),
+                        mutable: true,
+                        doc: None,
+                    })),
+                    stmt(StmtKind::Expression(get_identifier("c"))),
+                ]
+            }
+        );
+
+        let mut parser: Parser = Parser::init("fun f( { }");
+        let (_, errors): (Program, Vec<ParseError>) =
+            generate_ast_recover(&parser.parse().unwrap());
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+    }
+
+    #[test]
+    fn test_no_operation_statement_ast() {
+        let program: Program = generate_ast_from(";;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::NoOperation), stmt(StmtKind::NoOperation)]
             }
         )
     }
 
     #[test]
-    fn test_loop_statement_ast() {
-        // while(var_name) { --var_name; }
-        let keyword: &str = "while";
-        let unary_operator: &str = "--";
-        let variable_name: &str = "var_name";
-        let tokens: Vec<&str> = vec![
-            keyword,
-            "(",
-            variable_name,
-            ")",
-            "{",
-            unary_operator,
-            variable_name,
-            ";",
-            "}",
+    fn test_return_statement_ast() {
+        let program: Program = generate_ast_from("return var_name;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Return(Some(get_identifier("var_name"))))]
+            }
+        );
+
+        let program: Program = generate_ast_from("return; return x + 1;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![
+                    stmt(StmtKind::Return(None)),
+                    stmt(StmtKind::Return(Some(get_binary_expression(
+                        "+".to_string(),
+                        get_identifier("x"),
+                        get_integer("1")
+                    )))),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_loop_control_statement_ast() {
+        let program: Program = generate_ast_from("break; continue;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Break), stmt(StmtKind::Continue)]
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_semicolon_ast() {
+        let missing: Vec<(&str, ParseError)> = vec![
+            (
+                "return x }",
+                ParseError {
+                    message: "Expected ';' but found '}'".to_string(),
+                    span: 9..10,
+                },
+            ),
+            (
+                "return",
+                ParseError {
+                    message: "Expected Expression but found nothing".to_string(),
+                    span: 6..6,
+                },
+            ),
+            (
+                "break",
+                ParseError {
+                    message: "Expected ';' but found nothing".to_string(),
+                    span: 5..5,
+                },
+            ),
+            (
+                "continue x;",
+                ParseError {
+                    message: "Expected ';' but found 'x'".to_string(),
+                    span: 9..10,
+                },
+            ),
         ];
-        let program: Program = generate_ast(&tokens);
+        for (code, error) in missing {
+            assert_eq!(generate_ast_from(code), Err(error), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_nested_block_statement_ast() {
+        let program: Program = generate_ast_from("{{};}").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Block(vec![
+                    stmt(StmtKind::Block(vec![])),
+                    stmt(StmtKind::NoOperation)
+                ]))]
+            }
+        );
+    }
+
+    #[test]
+    fn test_block_statement_ast() {
+        let program: Program = generate_ast_from("{ let x = 1; x++; }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Block(vec![
+                    stmt(StmtKind::VarDecl(VarDecl {
+                        name: "x".to_string(),
+                        typ: DataType::None,
+                        value: Some(get_integer("1")),
+                        mutable: true,
+                        doc: None,
+                    })),
+                    stmt(StmtKind::Expression(expr(ExprKind::PostInc(Box::new(
+                        get_identifier("x")
+                    ))))),
+                ]))]
+            }
+        );
+
+        // Unclosed blocks point at their own opener, not the outermost one
+        for code in ["{ { a; ", "{ {"] {
+            assert_eq!(
+                generate_ast_from(code),
+                Err(ParseError {
+                    message: "Expected '}' to close this '{'".to_string(),
+                    span: 2..3,
+                })
+            );
+        }
+        assert_eq!(
+            generate_ast_from("fun main() { return;"),
+            Err(ParseError {
+                message: "Expected '}' to close this '{'".to_string(),
+                span: 11..12,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unary_expression_statement_ast() {
+        let program: Program = generate_ast_from("++var_name;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Expression(expr(ExprKind::PreInc(
+                    Box::new(get_identifier("var_name"))
+                ))))]
+            }
+        )
+    }
+
+    #[test]
+    fn test_increment_expression_ast() {
+        let prefix: Expr = parse_expression_from("++a").unwrap();
+        let postfix: Expr = parse_expression_from("a++").unwrap();
+        assert_eq!(
+            prefix,
+            expr(ExprKind::PreInc(Box::new(get_identifier("a"))))
+        );
+        assert_eq!(
+            postfix,
+            expr(ExprKind::PostInc(Box::new(get_identifier("a"))))
+        );
+        assert_ne!(prefix, postfix);
+        assert_eq!(
+            parse_expression_from("--a").unwrap(),
+            expr(ExprKind::PreDec(Box::new(get_identifier("a"))))
+        );
+        assert_eq!(
+            parse_expression_from("a--").unwrap(),
+            expr(ExprKind::PostDec(Box::new(get_identifier("a"))))
+        );
+        assert_eq!(
+            parse_expression_from("a[i]++ + ++b").unwrap().to_string(),
+            "((a[i]++) + (++b))"
+        );
+    }
+
+    #[test]
+    fn test_chained_postfix_expression_ast() {
+        assert_eq!(
+            parse_expression_from("a++--"),
+            Err(ParseError {
+                message: "Expected only one postfix operator but found '--'".to_string(),
+                span: 3..5,
+            })
+        );
+        assert_eq!(
+            parse_expression_from("a++ ++").unwrap_err().message,
+            "Expected only one postfix operator but found '++'"
+        );
+    }
+
+    #[test]
+    fn test_binary_expression_statement_ast() {
+        let program: Program = generate_ast_from("a + b * c;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::Expression(get_binary_expression(
+                    "+".to_string(),
+                    get_identifier("a"),
+                    get_binary_expression(
+                        "*".to_string(),
+                        get_identifier("b"),
+                        get_identifier("c")
+                    ),
+                )))]
+            }
+        );
+        assert_eq!(
+            generate_ast_from("a + b").unwrap_err().message,
+            "Expected ';' but found nothing"
+        );
+    }
+
+    #[test]
+    fn test_variable_assignment_statement_ast() {
+        let program: Program = generate_ast_from("int var_name = a * b;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::VarDecl(VarDecl {
+                    name: "var_name".to_string(),
+                    typ: DataType::Integer,
+                    value: Some(get_binary_expression(
+                        "*".to_string(),
+                        get_identifier("a"),
+                        get_identifier("b")
+                    )),
+                    mutable: true,
+                    doc: None,
+                }))]
+            }
+        )
+    }
+
+    #[test]
+    fn test_variable_declaration_statement_ast() {
+        let program: Program = generate_ast_from("let x: int = 42;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::VarDecl(VarDecl {
+                    name: "x".to_string(),
+                    typ: DataType::Integer,
+                    value: Some(get_integer("42")),
+                    mutable: true,
+                    doc: None,
+                }))]
+            }
+        );
+
+        // Type and initializer can both be left out
+        let program: Program = generate_ast_from("let y; let z = 1;").unwrap();
         assert_eq!(
             program,
             Program {
                 statements: vec![
-                    Statement {
-                        typ: StatementType::Loop,
-                        value: Some(keyword.to_string()),
-                        options: None,
-                        expression: Some(Expression::new(
-                            ExpressionType::Identifier,
-                            Some(variable_name.to_string()),
-                            None
-                        )),
-                        statements: Some(vec![Statement {
-                            typ: StatementType::Compound,
-                            value: None,
-                            options: None,
-                            expression: None,
-                            statements: Some(vec![
-                                Statement {
-                                    typ: StatementType::Expression,
-                                    value: None,
-                                    options: None,
-                                    expression: Some(Expression {
-                                        typ: ExpressionType::Unary,
-                                        value: Some(unary_operator.to_string()),
-                                        expressions: Some(vec![Expression {
-                                            typ: ExpressionType::Identifier,
-                                            value: Some(variable_name.to_string()),
-                                            expressions: None,
-                                        }]),
-                                    }),
-                                    statements: None,
-                                } // Statement (inner)
-                            ]) // Statement.statements (inner)
-                        }]), // Statement.statements (outer)
-                    } // Statement (outer)
-                ] // Program.statements
+                    stmt(StmtKind::VarDecl(VarDecl {
+                        name: "y".to_string(),
+                        typ: DataType::None,
+                        value: None,
+                        mutable: true,
+                        doc: None,
+                    })),
+                    stmt(StmtKind::VarDecl(VarDecl {
+                        name: "z".to_string(),
+                        typ: DataType::None,
+                        value: Some(get_integer("1")),
+                        mutable: true,
+                        doc: None,
+                    })),
+                ]
+            }
+        );
+        assert_eq!(
+            generate_ast_from("let x: = 1;").unwrap_err().message,
+            "Expected DataType but found '='"
+        );
+    }
+
+    #[test]
+    fn test_constant_declaration_statement_ast() {
+        let program: Program = generate_ast_from("const limit: float = 0.5;").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::VarDecl(VarDecl {
+                    name: "limit".to_string(),
+                    typ: DataType::Float,
+                    value: Some(expr(ExprKind::Literal {
+                        typ: DataType::Float,
+                        value: "0.5".to_string(),
+                    })),
+                    mutable: false,
+                    doc: None,
+                }))]
+            }
+        );
+        assert_eq!(
+            generate_ast_from("const limit: int;"),
+            Err(ParseError {
+                message: "Constant 'limit' must be initialized".to_string(),
+                span: 6..11,
+            })
+        );
+    }
+
+    #[test]
+    fn test_function_statement_ast() {
+        let program: Program =
+            generate_ast_from("fun name(a: int, b: str) -> int { return a; }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::FnDecl(FnDecl {
+                    name: "name".to_string(),
+                    parameters: vec![
+                        Parameter {
+                            name: "a".to_string(),
+                            typ: DataType::Integer,
+                            default: None,
+                            variadic: false,
+                        },
+                        Parameter {
+                            name: "b".to_string(),
+                            typ: DataType::String,
+                            default: None,
+                            variadic: false,
+                        },
+                    ],
+                    return_type: DataType::Integer,
+                    body: vec![stmt(StmtKind::Return(Some(get_identifier("a"))))],
+                    doc: None,
+                }))]
+            }
+        );
+
+        // Parameters can also be written like variable definitions
+        let program: Program = generate_ast_from("fun sum(int a, bool b) {}").unwrap();
+        let StmtKind::FnDecl(function) = &program.statements[0].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(
+            function.parameters,
+            vec![
+                Parameter {
+                    name: "a".to_string(),
+                    typ: DataType::Integer,
+                    default: None,
+                    variadic: false,
+                },
+                Parameter {
+                    name: "b".to_string(),
+                    typ: DataType::Boolean,
+                    default: None,
+                    variadic: false,
+                },
+            ]
+        );
+        assert_eq!(function.return_type, DataType::None);
+    }
+
+    #[test]
+    fn test_default_parameter_ast() {
+        let program: Program = generate_ast_from("fun f(a: int, b: int = 10) {}").unwrap();
+        let StmtKind::FnDecl(function) = &program.statements[0].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(
+            function.parameters,
+            vec![
+                Parameter {
+                    name: "a".to_string(),
+                    typ: DataType::Integer,
+                    default: None,
+                    variadic: false,
+                },
+                Parameter {
+                    name: "b".to_string(),
+                    typ: DataType::Integer,
+                    default: Some(get_integer("10")),
+                    variadic: false,
+                },
+            ]
+        );
+
+        // Defaults can be any constant expression
+        assert!(generate_ast_from("fun f(int a = -(1 + 2), b: str = \"s\") {}").is_ok());
+    }
+
+    #[test]
+    fn test_default_parameter_errors() {
+        assert_eq!(
+            generate_ast_from("fun f(a: int = 1, b: int) {}"),
+            Err(ParseError {
+                message:
+                    "Parameter 'b' without a default value can not follow one with a default value"
+                        .to_string(),
+                span: 18..24,
+            })
+        );
+        assert_eq!(
+            generate_ast_from("fun f(a: int = x + 1) {}"),
+            Err(ParseError {
+                message: "Default value of parameter 'a' must be a constant expression".to_string(),
+                span: 15..20,
+            })
+        );
+        assert_eq!(
+            generate_ast_from("fun f(a: int = g()) {}")
+                .unwrap_err()
+                .message,
+            "Default value of parameter 'a' must be a constant expression"
+        );
+    }
+
+    #[test]
+    fn test_variadic_parameter_ast() {
+        let program: Program = generate_ast_from("fun f(sep: str, args: int...) {}").unwrap();
+        let StmtKind::FnDecl(function) = &program.statements[0].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(
+            function.parameters[1],
+            Parameter {
+                name: "args".to_string(),
+                typ: DataType::Integer,
+                default: None,
+                variadic: true,
+            }
+        );
+        assert!(!function.parameters[0].variadic);
+
+        // Variadic parameters can follow ones with a default value
+        assert!(generate_ast_from("fun f(a: int = 0, rest: int...) {}").is_ok());
+    }
+
+    #[test]
+    fn test_variadic_parameter_errors() {
+        assert_eq!(
+            generate_ast_from("fun f(args: int..., last: int) {}"),
+            Err(ParseError {
+                message: "Only the last parameter can be variadic but 'last' follows one"
+                    .to_string(),
+                span: 20..29,
+            })
+        );
+        assert_eq!(
+            generate_ast_from("fun f(args: int... = 1) {}")
+                .unwrap_err()
+                .message,
+            "Variadic parameter 'args' can not have a default value"
+        );
+    }
+
+    #[test]
+    fn test_array_type_ast() {
+        let get_typ = |code: &str| -> DataType {
+            let program: Program = generate_ast_from(code).unwrap();
+            let StmtKind::VarDecl(declaration) = &program.statements[0].kind else {
+                panic!("Expected VarDecl but got {:?}", program.statements[0]);
+            };
+            return declaration.typ.clone();
+        };
+        let int_array: DataType = DataType::Array(ArrayType {
+            elem: Box::new(DataType::Integer),
+            size: None,
+        });
+        assert_eq!(get_typ("let a: [int] = [1, 2];"), int_array);
+        assert_eq!(
+            get_typ("let b: [int; 5] = [];"),
+            DataType::Array(ArrayType {
+                elem: Box::new(DataType::Integer),
+                size: Some(Box::new(get_integer("5"))),
+            })
+        );
+        assert_eq!(
+            get_typ("let c: [[int]];"),
+            DataType::Array(ArrayType {
+                elem: Box::new(int_array.clone()),
+                size: None,
+            })
+        );
+
+        // Parameters and return types can be arrays too
+        let program: Program = generate_ast_from("fun f(a: [int]) -> [str; 2] {}").unwrap();
+        let StmtKind::FnDecl(function) = &program.statements[0].kind else {
+            panic!("Expected FnDecl but got {:?}", program.statements[0]);
+        };
+        assert_eq!(function.parameters[0].typ, int_array);
+        assert_eq!(function.return_type.to_string(), "[str; 2]");
+        assert_eq!(
+            get_typ("let d: [[bool; 2 * 2]];").to_string(),
+            "[[bool; (2 * 2)]]"
+        );
+
+        assert!(generate_ast_from("let e: [int = [];").is_err());
+        assert!(generate_ast_from("let f: [] = [];").is_err());
+    }
+
+    #[test]
+    fn test_struct_ast() {
+        let program: Program = generate_ast_from("struct Point { x: int, y: [float] }").unwrap();
+        assert_eq!(
+            program.statements,
+            vec![stmt(StmtKind::StructDecl(StructDecl {
+                name: "Point".to_string(),
+                fields: vec![
+                    Field {
+                        name: "x".to_string(),
+                        typ: DataType::Integer,
+                    },
+                    Field {
+                        name: "y".to_string(),
+                        typ: DataType::Array(ArrayType {
+                            elem: Box::new(DataType::Float),
+                            size: None,
+                        }),
+                    },
+                ],
+            }))]
+        );
+        assert_eq!(
+            parse_expression_from("Point { x: 1, y: a }"),
+            Ok(expr(ExprKind::StructLit {
+                name: "Point".to_string(),
+                fields: vec![
+                    ("x".to_string(), get_integer("1")),
+                    ("y".to_string(), get_identifier("a")),
+                ],
+            }))
+        );
+        assert_eq!(
+            parse_expression_from("p.x"),
+            Ok(expr(ExprKind::FieldAccess {
+                target: Box::new(get_identifier("p")),
+                field: "x".to_string(),
+            }))
+        );
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("a.b.c", "a.b.c"),
+            ("f().x[0]", "f().x[0]"),
+            (
+                "Line { from: Point { x: 0 }, to: p }.to.x",
+                "Line { from: Point { x: 0 }, to: p }.to.x",
+            ),
+            ("-p.x + 1", "((-p.x) + 1)"),
+        ];
+        for (code, expression) in expressions {
+            assert_eq!(
+                parse_expression_from(code).unwrap().to_string(),
+                expression,
+                "{}",
+                code
+            );
+        }
+
+        // Blocks after a condition are not struct literals
+        let program: Program = generate_ast_from("if a { b; } while c {}").unwrap();
+        assert!(matches!(program.statements[0].kind, StmtKind::If { .. }));
+        assert!(matches!(program.statements[1].kind, StmtKind::While { .. }));
+
+        assert_eq!(
+            generate_ast_from("struct P { x: int, y: int, x: float }"),
+            Err(ParseError {
+                message: "Field 'x' is already defined in struct 'P'".to_string(),
+                span: 27..28,
+            })
+        );
+        assert!(generate_ast_from("struct P { x }").is_err());
+        assert!(parse_expression_from("p.1").is_err());
+    }
+
+    #[test]
+    fn test_enum_ast() {
+        let program: Program = generate_ast_from("enum Color { Red, Green, Blue }").unwrap();
+        assert_eq!(
+            program.statements,
+            vec![stmt(StmtKind::EnumDecl(EnumDecl {
+                name: "Color".to_string(),
+                variants: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+            }))]
+        );
+        assert_eq!(
+            parse_expression_from("Color::Red"),
+            Ok(expr(ExprKind::EnumVariant {
+                name: "Color".to_string(),
+                variant: "Red".to_string(),
+            }))
+        );
+        assert_eq!(
+            parse_expression_from("c == Color::Green")
+                .unwrap()
+                .to_string(),
+            "(c == Color::Green)"
+        );
+
+        assert_eq!(
+            generate_ast_from("enum Color { Red, Green, Red }"),
+            Err(ParseError {
+                message: "Variant 'Red' is already defined in enum 'Color'".to_string(),
+                span: 25..28,
+            })
+        );
+        assert!(generate_ast_from("enum Color { Red Green }").is_err());
+        assert!(parse_expression_from("Color::").is_err());
+    }
+
+    #[test]
+    fn test_match_ast() {
+        assert_eq!(
+            parse_expression_from("match x { 1 => a, 2 => { b; }, _ => c }"),
+            Ok(expr(ExprKind::Match {
+                scrutinee: Box::new(get_identifier("x")),
+                arms: vec![
+                    MatchArm {
+                        pattern: Pattern::Value(get_integer("1")),
+                        body: ArmBody::Expression(get_identifier("a")),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Value(get_integer("2")),
+                        body: ArmBody::Block(vec![stmt(StmtKind::Expression(get_identifier("b")))]),
+                    },
+                    MatchArm {
+                        pattern: Pattern::Wildcard,
+                        body: ArmBody::Expression(get_identifier("c")),
+                    },
+                ],
+            }))
+        );
+        let program: Program = generate_ast_from("let y = match x { _ => 0 };").unwrap();
+        assert_eq!(
+            program.statements[0].to_string(),
+            "let y = match x { _ => 0 };"
+        );
+
+        let expressions: Vec<(&str, &str)> = vec![
+            (
+                "match c { Color::Red => 1, -1 => x = 2 }",
+                "match c { Color::Red => 1, (-1) => (x = 2) }",
+            ),
+            ("match a + b {}", "match (a + b) {}"),
+            (
+                "match x { _ => match y { _ => z } }",
+                "match x { _ => match y { _ => z } }",
+            ),
+        ];
+        for (code, expression) in expressions {
+            assert_eq!(
+                parse_expression_from(code).unwrap().to_string(),
+                expression,
+                "{}",
+                code
+            );
+        }
+
+        assert!(parse_expression_from("match x { 1 => a 2 => b }").is_err());
+        assert!(parse_expression_from("match x { 1 a }").is_err());
+        assert!(parse_expression_from("match x { _ => a").is_err());
+    }
+
+    #[test]
+    fn test_lambda_ast() {
+        let get_parameter = |name: &str, typ: DataType| -> Parameter {
+            Parameter {
+                name: name.to_string(),
+                typ: typ,
+                default: None,
+                variadic: false,
+            }
+        };
+        // Last Expression of a block body without ';' is its return value
+        assert_eq!(
+            parse_expression_from("|a: int, b| -> int { a; a + b }"),
+            Ok(expr(ExprKind::Lambda {
+                parameters: vec![
+                    get_parameter("a", DataType::Integer),
+                    get_parameter("b", DataType::None)
+                ],
+                return_type: DataType::Integer,
+                body: LambdaBody::Block(vec![
+                    stmt(StmtKind::Expression(get_identifier("a"))),
+                    stmt(StmtKind::Return(Some(expr(ExprKind::Binary {
+                        operator: "+".to_string(),
+                        left: Box::new(get_identifier("a")),
+                        right: Box::new(get_identifier("b")),
+                    }))))
+                ]),
+            }))
+        );
+        assert_eq!(
+            parse_expression_from("|| 42"),
+            Ok(expr(ExprKind::Lambda {
+                parameters: vec![],
+                return_type: DataType::None,
+                body: LambdaBody::Expression(Box::new(get_integer("42"))),
+            }))
+        );
+
+        let lambdas: Vec<(&str, &str)> = vec![
+            ("|x| x + 1", "|x| (x + 1)"),
+            ("f(|x: [int]| x[0], 2)", "f(|x: [int]| x[0], 2)"),
+            ("|| { let y = 1; return y; }", "|| { let y = 1; return y; }"),
+            ("|x| |y| x * y", "|x| |y| (x * y)"),
+        ];
+        for (code, lambda) in lambdas {
+            assert_eq!(
+                parse_expression_from(code).unwrap().to_string(),
+                lambda,
+                "{}",
+                code
+            );
+        }
+
+        assert!(parse_expression_from("|x").is_err());
+        assert!(parse_expression_from("|x, 1| x").is_err());
+        assert!(parse_expression_from("|| { 1 2 }").is_err());
+    }
+
+    #[test]
+    fn test_main_function_statement_ast() {
+        let program: Program = generate_ast_from("fun main() { }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::FnDecl(FnDecl {
+                    name: "main".to_string(),
+                    parameters: vec![],
+                    return_type: DataType::None,
+                    body: vec![],
+                    doc: None,
+                }))]
+            }
+        );
+
+        let code: &str = "fun main(a: int,) {}";
+        assert_eq!(
+            generate_ast_from(code),
+            Err(ParseError {
+                message: "Expected Identifier but found ')'".to_string(),
+                span: 16..17,
+            })
+        );
+        let code: &str = "fun main() -> {}";
+        assert_eq!(
+            generate_ast_from(code).unwrap_err().message,
+            "Expected DataType but found '{'"
+        );
+    }
+
+    #[test]
+    fn test_conditional_statement_ast() {
+        let program: Program = generate_ast_from("if(var_name) { return var_name; }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::If {
+                    condition: expr(ExprKind::Grouping(Box::new(get_identifier("var_name")))),
+                    body: vec![stmt(StmtKind::Return(Some(get_identifier("var_name"))))],
+                    elifs: vec![],
+                    else_body: None,
+                })]
+            }
+        )
+    }
+
+    #[test]
+    fn test_conditional_chain_statement_ast() {
+        let program: Program = generate_ast_from("if a { } elif b { } else { }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::If {
+                    condition: get_identifier("a"),
+                    body: vec![],
+                    elifs: vec![(get_identifier("b"), vec![])],
+                    else_body: Some(vec![]),
+                })]
+            }
+        );
+
+        // Nested chains end at the closing curly bracket of their branch
+        let code: &str = "if a { if b { } else { c; } } elif d { } elif e { }";
+        let program: Program = generate_ast_from(code).unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::If {
+                    condition: get_identifier("a"),
+                    body: vec![stmt(StmtKind::If {
+                        condition: get_identifier("b"),
+                        body: vec![],
+                        elifs: vec![],
+                        else_body: Some(vec![stmt(StmtKind::Expression(get_identifier("c")))]),
+                    })],
+                    elifs: vec![(get_identifier("d"), vec![]), (get_identifier("e"), vec![]),],
+                    else_body: None,
+                })]
+            }
+        );
+        assert_eq!(
+            generate_ast_from("if a { } ; else { }"),
+            Err(ParseError {
+                message: "Expected 'if' before 'else'".to_string(),
+                span: 11..15,
+            })
+        );
+    }
+
+    #[test]
+    fn test_loop_statement_ast() {
+        let program: Program = generate_ast_from("while(var_name) { --var_name; }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::While {
+                    condition: expr(ExprKind::Grouping(Box::new(get_identifier("var_name")))),
+                    body: vec![stmt(StmtKind::Expression(expr(ExprKind::PreDec(
+                        Box::new(get_identifier("var_name"))
+                    ))))],
+                })]
             }
         )
     }
+
+    #[test]
+    fn test_while_statement_ast() {
+        let program: Program = generate_ast_from("while i < 10 { i++; }").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![stmt(StmtKind::While {
+                    condition: get_binary_expression(
+                        "<".to_string(),
+                        get_identifier("i"),
+                        get_integer("10")
+                    ),
+                    body: vec![stmt(StmtKind::Expression(expr(ExprKind::PostInc(
+                        Box::new(get_identifier("i"))
+                    ))))],
+                })]
+            }
+        );
+
+        let program: Program =
+            generate_ast_from("while true { break; continue; } while a {}").unwrap();
+        assert_eq!(
+            program,
+            Program {
+                statements: vec![
+                    stmt(StmtKind::While {
+                        condition: expr(ExprKind::Literal {
+                            typ: DataType::Boolean,
+                            value: "true".to_string(),
+                        }),
+                        body: vec![stmt(StmtKind::Break), stmt(StmtKind::Continue)],
+                    }),
+                    stmt(StmtKind::While {
+                        condition: get_identifier("a"),
+                        body: vec![],
+                    }),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_for_statement_ast() {
+        let program: Program = generate_ast_from("for (let i = 0; i < n; i++) { f(i); }").unwrap();
+        assert_eq!(
+            program.statements,
+            vec![stmt(StmtKind::For {
+                init: Some(Box::new(stmt(StmtKind::VarDecl(VarDecl {
+                    name: "i".to_string(),
+                    typ: DataType::None,
+                    value: Some(get_integer("0")),
+                    mutable: true,
+                    doc: None,
+                })))),
+                condition: Some(get_binary_expression(
+                    "<".to_string(),
+                    get_identifier("i"),
+                    get_identifier("n")
+                )),
+                step: Some(expr(ExprKind::PostInc(Box::new(get_identifier("i"))))),
+                body: vec![stmt(StmtKind::Expression(expr(ExprKind::Call {
+                    callee: Box::new(get_identifier("f")),
+                    arguments: vec![get_identifier("i")],
+                })))],
+            })]
+        );
+        assert_eq!(
+            generate_ast_from("for (;;) {}").unwrap().statements,
+            vec![stmt(StmtKind::For {
+                init: None,
+                condition: None,
+                step: None,
+                body: vec![],
+            })]
+        );
+
+        let loops: Vec<(&str, &str)> = vec![
+            ("for (i = 0;;) {}", "for ((i = 0);;) {}"),
+            ("for (int i = 0; i;) {}", "for (let i: int = 0; i;) {}"),
+            ("for (; a; b++) { break; }", "for (; a; (b++)) { break; }"),
+        ];
+        for (code, rendered) in loops {
+            let program: Program = generate_ast_from(code).unwrap();
+            assert_eq!(program.statements[0].to_string(), rendered, "{}", code);
+        }
+
+        assert_eq!(
+            generate_ast_from("for ({} ;;) {}"),
+            Err(ParseError {
+                message: "Expected declaration or Expression to initialize 'for'".to_string(),
+                span: 5..7,
+            })
+        );
+        assert!(generate_ast_from("for (;) {}").is_err());
+        assert!(generate_ast_from("for (;;) ;").is_err());
+
+        // Code ending inside the header is an error instead of a panic
+        assert_eq!(
+            generate_ast_from("for ("),
+            Err(ParseError {
+                message: "Expected declaration or Expression to initialize 'for'".to_string(),
+                span: 5..5,
+            })
+        );
+        assert!(generate_ast_from("for (;").is_err());
+    }
+
+    #[test]
+    fn test_for_in_statement_ast() {
+        let program: Program = generate_ast_from("for x in 0..10 { f(x); }").unwrap();
+        assert_eq!(
+            program.statements,
+            vec![stmt(StmtKind::ForIn {
+                variable: "x".to_string(),
+                iterable: get_binary_expression(
+                    "..".to_string(),
+                    get_integer("0"),
+                    get_integer("10")
+                ),
+                body: vec![stmt(StmtKind::Expression(expr(ExprKind::Call {
+                    callee: Box::new(get_identifier("f")),
+                    arguments: vec![get_identifier("x")],
+                })))],
+            })]
+        );
+
+        let loops: Vec<(&str, &str)> = vec![
+            ("for e in [1, 2] {}", "for e in [1, 2] {}"),
+            (
+                "for i in 1..=n - 1 { break; }",
+                "for i in (1 ..= (n - 1)) { break; }",
+            ),
+            ("for in in in {}", "for in in in {}"),
+        ];
+        for (code, rendered) in loops {
+            let program: Program = generate_ast_from(code).unwrap();
+            assert_eq!(program.statements[0].to_string(), rendered, "{}", code);
+        }
+
+        assert!(generate_ast_from("for x 0..10 {}").is_err());
+        assert!(generate_ast_from("for 1 in a {}").is_err());
+    }
+
+    // Counts binary operations, leaving every other node to the default walk
+    struct BinaryCounter {
+        count: usize,
+    }
+
+    impl Visitor for BinaryCounter {
+        fn visit_expr(&mut self, expression: &Expr) {
+            if let ExprKind::Binary { .. } = expression.kind {
+                self.count += 1;
+            }
+            walk_expr(self, expression);
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let code: &str = "fun main() { let x = 1 + 2 * 3; while x < 10 { f(x[0] - 1); } }
+            if a { } elif b == c { } else { return -(d & e); } [1 ** 2];";
+        let program: Program = generate_ast_from(code).unwrap();
+        let mut counter: BinaryCounter = BinaryCounter { count: 0 };
+        walk_program(&mut counter, &program);
+        assert_eq!(counter.count, 7);
+    }
 }
@@ -1,328 +1,1254 @@
-#[allow(dead_code)]
+use crate::intern::Symbol;
+use once_cell::sync::Lazy;
 use phf::phf_ordered_map;
+use regex::Regex;
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
 
-pub(crate) const DATATYPES: [&str; 3] = ["char", "int", "str"];
+pub(crate) const DATATYPES: [&str; 6] = ["bool", "char", "float", "int", "str", "void"];
 
-pub(crate) const EXPRESSION_DELIMITERS: [&str; 7] = [";", ")", "[", "]", "(", ")", ","];
-
-pub(crate) const BINARY_OPERATORS: [&str; 15] = [
-    "+", "-", "*", "/", "=", "+=", "-=", "*=", "/=", "==", "!=", ">", ">=", "<", "<=",
+// Note: '&' is lexed as UnaryOperator and is a bitwise AND only between two operands
+// '?' is the start of a Ternary, its ':' is lexed as a Delimiter
+pub(crate) const BINARY_OPERATORS: [&str; 34] = [
+    "+", "-", "*", "/", "%", "=", "+=", "-=", "*=", "/=", "%=", "==", "!=", ">", ">=", "<", "<=",
+    "&&", "||", "&", "|", "^", "<<", ">>", "&=", "|=", "^=", "<<=", ">>=", "..", "..=", "**", "?",
+    "??",
 ];
 
+pub(crate) const UNARY_OPERATORS: [&str; 4] = ["++", "--", "&", "!"];
+
+// Operator-like code that is always a typo, since there is no prefix '<'
+pub(crate) const MISTYPED_OPERATORS: [&str; 1] = ["=<"];
+
+// Patterns are anchored to the cursor when compiled, so they must not start with '^'
+// If a pattern has a capture group, the group is the Token and the rest is only lookahead
 pub(crate) const TOKEN_REGEXES: phf::OrderedMap<&str, TokenType> = phf_ordered_map!(
   // Whitespace
-  r"^\s+"           => TokenType::None,
+  r"\s+"            => TokenType::None,
 
-  // Comments
-  r"^//.*"          => TokenType::None, // Single-line comment
-  r"^/\*[\s\S]*?\*/"=> TokenType::None, // Multi-line comment
+  // Comments, multi-line ones are scanned by the Tokenizer
+  r"//.*"           => TokenType::None, // Single-line comment
+  r"#.*"            => TokenType::None, // Hash comment, '#[' may later start an attribute
 
   // Literals
-  r"^'[^']'"        => TokenType::Literal(DataType::Character),
-  r"^\d+"           => TokenType::Literal(DataType::Integer),
-  r#"^"[^"]*""#     => TokenType::Literal(DataType::String),
+  r"'(?:[^'\\\n]|\\[^\n])*'" => TokenType::Literal(DataType::Character),
+  // Digits may be separated by single underscores, e.g. 1_000_000
+  r"0x[0-9a-fA-F](?:_?[0-9a-fA-F])*" => TokenType::Literal(DataType::Integer), // Hexadecimal
+  r"0b[01](?:_?[01])*" => TokenType::Literal(DataType::Integer), // Binary
+  r"0o[0-7](?:_?[0-7])*" => TokenType::Literal(DataType::Integer), // Octal
+  r"[0-9](?:_?[0-9])*(?:\.[0-9](?:_?[0-9])*)?[eE][+-]?[0-9](?:_?[0-9])*" => TokenType::Literal(DataType::Float), // Scientific notation
+  r"[0-9](?:_?[0-9])*\.[0-9](?:_?[0-9])*" => TokenType::Literal(DataType::Float),
+  r"[0-9](?:_?[0-9])*" => TokenType::Literal(DataType::Integer),
+  r#""(?:[^"\\]|\\[\s\S])*""# => TokenType::Literal(DataType::String),
+  r"true\b"         => TokenType::Bool,
+  r"false\b"        => TokenType::Bool,
+  r"none\b"         => TokenType::Null,
+
+  // Word boundaries keep identifiers like 'format' from matching a DataType or Keyword
 
   // Datatypes
-  r"^char"          => TokenType::DataType,
-  r"^int"           => TokenType::DataType,
-  r"^str"           => TokenType::DataType,
+  r"bool\b"         => TokenType::DataType,
+  r"char\b"         => TokenType::DataType,
+  r"float\b"        => TokenType::DataType,
+  r"int\b"          => TokenType::DataType,
+  r"str\b"          => TokenType::DataType,
+  r"void\b"         => TokenType::DataType,
 
   // Keywords
-  r"^break"         => TokenType::Keyword,
-  r"^continue"      => TokenType::Keyword,
-  r"^elif"          => TokenType::Keyword,
-  r"^else"          => TokenType::Keyword,
-  r"^fun"           => TokenType::Keyword,
-  r"^if"            => TokenType::Keyword,
-  r"^return"        => TokenType::Keyword,
-  r"^while"         => TokenType::Keyword,
+  r"break\b"        => TokenType::Keyword,
+  r"const\b"        => TokenType::Keyword,
+  r"continue\b"     => TokenType::Keyword,
+  r"elif\b"         => TokenType::Keyword,
+  r"else\b"         => TokenType::Keyword,
+  r"enum\b"         => TokenType::Keyword,
+  r"for\b"          => TokenType::Keyword,
+  r"fun\b"          => TokenType::Keyword,
+  r"if\b"           => TokenType::Keyword,
+  r"import\b"       => TokenType::Keyword,
+  r"let\b"          => TokenType::Keyword,
+  r"match\b"        => TokenType::Keyword,
+  r"mod\b"          => TokenType::Keyword,
+  r"return\b"       => TokenType::Keyword,
+  r"struct\b"       => TokenType::Keyword,
+  r"while\b"        => TokenType::Keyword,
 
   // Unary Operators
-  r"^\+\+"          => TokenType::UnaryOperator,    // Increment
-  r"^--"            => TokenType::UnaryOperator,    // Decrement
-  r"^&"             => TokenType::UnaryOperator,    // Address
-  r"^(!)[^=]"       => TokenType::UnaryOperator,    // Logical negation
+  r"\+\+"           => TokenType::UnaryOperator,    // Increment
+  r"--"             => TokenType::UnaryOperator,    // Decrement
+  r"(&)(?:[^&=]|$)" => TokenType::UnaryOperator,    // Address
+  r"(!)(?:[^=]|$)"  => TokenType::UnaryOperator,    // Logical negation
 
   // Delimiters
-  r"^\}"            => TokenType::Delimiter,
-  r"^\)"            => TokenType::Delimiter,
-  r"^\]"            => TokenType::Delimiter,
-  r"^:"             => TokenType::Delimiter,
-  r"^,"             => TokenType::Delimiter,
-  r"^\{"            => TokenType::Delimiter,
-  r"^\("            => TokenType::Delimiter,
-  r"^\["            => TokenType::Delimiter,
-  r"^;"             => TokenType::Delimiter,
-  r"^->"            => TokenType::Delimiter,
+  r"\}"             => TokenType::Delimiter,
+  r"\)"             => TokenType::Delimiter,
+  r"\]"             => TokenType::Delimiter,
+  r"::"             => TokenType::Delimiter,     // Path separator, before ':'
+  r":"              => TokenType::Delimiter,
+  r","              => TokenType::Delimiter,
+  r"\{"             => TokenType::Delimiter,
+  r"\("             => TokenType::Delimiter,
+  r"\["             => TokenType::Delimiter,
+  r";"              => TokenType::Delimiter,
+  r"->"             => TokenType::Delimiter,
+  r"=>"             => TokenType::Delimiter,     // Match arm, before '=' and '>='
+
+  r"\.\.\."         => TokenType::Delimiter,     // Variadic parameter
+  r"(\.)(?:[^.]|$)"  => TokenType::Delimiter,     // Member access, '..' is a range
 
   // Binary Operators
-  r"^=="            => TokenType::BinaryOperator,   // Equals
-  r"^="             => TokenType::BinaryOperator,   // Simple assignment
-  r"^\+="           => TokenType::BinaryOperator,   // Addition assignment
-  r"^-="            => TokenType::BinaryOperator,   // Substraction assignment
-  r"^\*="           => TokenType::BinaryOperator,   // Multiplication assignment
-  r"^/="            => TokenType::BinaryOperator,   // Division assignment
-  r"^\+"            => TokenType::BinaryOperator,   // Addition
-  r"^/"             => TokenType::BinaryOperator,   // Division
-  r"^>="            => TokenType::BinaryOperator,   // GreaterOrEqual
-  r"^>"             => TokenType::BinaryOperator,   // GreaterThan
-  r"^<="            => TokenType::BinaryOperator,   // LessOrEqual
-  r"^<"             => TokenType::BinaryOperator,   // LessThan
-  r"^\*"            => TokenType::BinaryOperator,   // Multiplication
-  r"^!="            => TokenType::BinaryOperator,   // NotEquals
-  r"^-"             => TokenType::BinaryOperator,   // Substraction
+  r"=="             => TokenType::BinaryOperator,   // Equals
+  r"="              => TokenType::BinaryOperator,   // Simple assignment
+  r"\+="            => TokenType::BinaryOperator,   // Addition assignment
+  r"-="             => TokenType::BinaryOperator,   // Substraction assignment
+  r"\*\*"           => TokenType::BinaryOperator,   // Exponentiation
+  r"\*="            => TokenType::BinaryOperator,   // Multiplication assignment
+  r"/="             => TokenType::BinaryOperator,   // Division assignment
+  r"%="             => TokenType::BinaryOperator,   // Remainder assignment
+  r"&="             => TokenType::BinaryOperator,   // BitwiseAnd assignment
+  r"\|="            => TokenType::BinaryOperator,   // BitwiseOr assignment
+  r"\^="            => TokenType::BinaryOperator,   // BitwiseXor assignment
+  r"<<="            => TokenType::BinaryOperator,   // LeftShift assignment
+  r">>="            => TokenType::BinaryOperator,   // RightShift assignment
+  r"\+"             => TokenType::BinaryOperator,   // Addition
+  r"/"              => TokenType::BinaryOperator,   // Division
+  r"%"              => TokenType::BinaryOperator,   // Remainder
+  r"<<"             => TokenType::BinaryOperator,   // LeftShift
+  r">>"             => TokenType::BinaryOperator,   // RightShift
+  r">="             => TokenType::BinaryOperator,   // GreaterOrEqual
+  r">"              => TokenType::BinaryOperator,   // GreaterThan
+  r"<="             => TokenType::BinaryOperator,   // LessOrEqual
+  r"<"              => TokenType::BinaryOperator,   // LessThan
+  r"\*"             => TokenType::BinaryOperator,   // Multiplication
+  r"!="             => TokenType::BinaryOperator,   // NotEquals
+  r"-"              => TokenType::BinaryOperator,   // Substraction
+  r"&&"             => TokenType::BinaryOperator,   // LogicalAnd
+  r"\|\|"           => TokenType::BinaryOperator,   // LogicalOr
+  r"\|"             => TokenType::BinaryOperator,   // BitwiseOr
+  r"\^"             => TokenType::BinaryOperator,   // BitwiseXor
+  r"\.\.="           => TokenType::BinaryOperator,   // InclusiveRange
+  r"\.\."            => TokenType::BinaryOperator,   // Range
+  r"\?\?"           => TokenType::BinaryOperator,   // NullCoalescing, before '?'
+  r"\?"             => TokenType::BinaryOperator,   // Ternary condition
 
   // Identifier - Named value representing some value or other entity
-  r"^[a-zA-Z_$][a-zA-Z_$0-9]*"  => TokenType::Identifier,
+  // Follows Unicode Standard Annex #31 with '_' and '$' allowed anywhere
+  r"[\p{XID_Start}_$][\p{XID_Continue}$]*" => TokenType::Identifier,
 );
 
-#[derive(Debug, PartialEq)]
+// TOKEN_REGEXES compiled once on first use, in the same order
+// Each pattern is anchored so it can only match at the start of the unparsed code
+pub(crate) static COMPILED_TOKEN_REGEXES: Lazy<Vec<(Regex, &'static TokenType)>> =
+    Lazy::new(|| {
+        TOKEN_REGEXES
+            .entries()
+            .map(|(regex, token_type)| {
+                let anchored_regex: String = format!("^(?:{})", regex);
+                (Regex::new(&anchored_regex).unwrap(), token_type)
+            })
+            .collect()
+    });
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Token {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub(crate) token_type: TokenType,
+    pub(crate) lexeme: Lexeme,
+    // Byte range of the lexeme in the code
+    pub(crate) span: Range<usize>,
+    // Position of the first character, both starting from 1
+    pub(crate) line: usize,
+    #[cfg_attr(feature = "serde", serde(rename = "col"))]
+    pub(crate) column: usize,
+    // Decimal value of integer literals and unescaped content of characters and strings
+    pub(crate) value: Option<String>,
+}
+
+// Identifiers and keywords repeat throughout the code so they are interned
+// The other lexemes, like literals and comments, keep their own text
+#[derive(Clone)]
+pub(crate) enum Lexeme {
+    Symbol(Symbol),
+    Text(String),
+}
+
+impl Lexeme {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Lexeme::Symbol(symbol) => symbol.as_str(),
+            Lexeme::Text(text) => text.as_str(),
+        }
+    }
+}
+
+impl PartialEq for Lexeme {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Lexeme::Symbol(symbol), Lexeme::Symbol(other)) => symbol == other,
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl PartialEq<str> for Lexeme {
+    fn eq(&self, other: &str) -> bool {
+        return self.as_str() == other;
+    }
+}
+
+impl PartialEq<&str> for Lexeme {
+    fn eq(&self, other: &&str) -> bool {
+        return self.as_str() == *other;
+    }
+}
+
+impl fmt::Debug for Lexeme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for Lexeme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// Lexemes are serialized as their text, Symbols are only valid in the current process
+#[cfg(feature = "serde")]
+impl serde::Serialize for Lexeme {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(self.as_str());
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Lexeme {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return Ok(Lexeme::Text(String::deserialize(deserializer)?));
+    }
+}
+
+// Change to the code, replacing the byte range of the old code with the text
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Edit {
+    pub(crate) range: Range<usize>,
+    pub(crate) text: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum TokenType {
+    // Never lexed, assignments like '+=' are lexed as binary operators
+    #[allow(dead_code)]
     AssignmentOperator,
     BinaryOperator,
+    // 'true' and 'false', parsed as boolean literals
+    Bool,
     DataType,
     Delimiter,
     Identifier,
     Literal(DataType),
     Keyword,
+    // The absence of a value, 'none'
+    Null,
     UnaryOperator,
     None,
 }
 
+// Equal TokenTypes always have the same kind of DataType, so hashing the kinds is enough
+impl Eq for TokenType {}
+
+impl Hash for TokenType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let TokenType::Literal(typ) = self {
+            mem::discriminant(typ).hash(state);
+        }
+    }
+}
+
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenType::AssignmentOperator => write!(f, "assignment operator"),
+            TokenType::BinaryOperator => write!(f, "binary operator"),
+            TokenType::Bool => write!(f, "boolean literal"),
+            TokenType::DataType => write!(f, "data type"),
+            TokenType::Delimiter => write!(f, "delimiter"),
+            TokenType::Identifier => write!(f, "identifier"),
+            TokenType::Literal(DataType::Character) => write!(f, "character literal"),
+            TokenType::Literal(DataType::Float) => write!(f, "float literal"),
+            TokenType::Literal(DataType::Integer) => write!(f, "integer literal"),
+            TokenType::Literal(DataType::String) => write!(f, "string literal"),
+            TokenType::Literal(
+                DataType::Array(_) | DataType::Boolean | DataType::None | DataType::Void,
+            ) => {
+                write!(f, "literal")
+            }
+            TokenType::Keyword => write!(f, "keyword"),
+            TokenType::Null => write!(f, "none"),
+            TokenType::UnaryOperator => write!(f, "unary operator"),
+            TokenType::None => write!(f, "whitespace or comment"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum LexError {
+    // Byte offset of the unknown Token and the code following it
+    // The suggestion is a known operator a single edit away from the unknown one
+    UnknownToken {
+        offset: usize,
+        snippet: String,
+        suggestion: Option<String>,
+    },
+    // Numeric literal directly followed by identifier characters, e.g. '1e' or '2abc'
+    InvalidNumber {
+        span: Range<usize>,
+        literal: String,
+    },
+    // Integer literal too large for a 64-bit signed integer, e.g. '9223372036854775808'
+    IntegerOverflow {
+        span: Range<usize>,
+        literal: String,
+    },
+    // Unknown escape sequence inside a literal, e.g. '\q'
+    InvalidEscape {
+        span: Range<usize>,
+        escape: String,
+    },
+    // Character literal not containing exactly one character, e.g. '' or 'ab'
+    InvalidCharacter {
+        span: Range<usize>,
+        literal: String,
+    },
+    // String literal missing its closing quote, starting from the opening one
+    UnterminatedString {
+        start: usize,
+    },
+    // Character literal missing its closing quote on the same line
+    UnterminatedCharacter {
+        start: usize,
+    },
+    // Block comment without the '*/' closing it
+    UnterminatedComment {
+        start: usize,
+    },
+    // Reading the code from a stream failed, e.g. because it is not valid UTF-8
+    Read {
+        offset: usize,
+        message: String,
+    },
+}
+
+impl LexError {
+    pub(crate) fn span(&self) -> Range<usize> {
+        match self {
+            LexError::UnknownToken { offset, .. } => *offset..*offset,
+            LexError::InvalidNumber { span, .. } => span.clone(),
+            LexError::IntegerOverflow { span, .. } => span.clone(),
+            LexError::InvalidEscape { span, .. } => span.clone(),
+            LexError::InvalidCharacter { span, .. } => span.clone(),
+            LexError::UnterminatedString { start } => *start..*start + 1,
+            LexError::UnterminatedCharacter { start } => *start..*start + 1,
+            LexError::UnterminatedComment { start } => *start..*start + 2,
+            LexError::Read { offset, .. } => *offset..*offset,
+        }
+    }
+
+    // Move the error by 'offset' bytes, for code lexed apart from the code before it
+    pub(crate) fn offset_by(self, offset: usize) -> Self {
+        let shift = |span: Range<usize>| span.start + offset..span.end + offset;
+        match self {
+            LexError::UnknownToken {
+                offset: start,
+                snippet,
+                suggestion,
+            } => LexError::UnknownToken {
+                offset: start + offset,
+                snippet: snippet,
+                suggestion: suggestion,
+            },
+            LexError::InvalidNumber { span, literal } => LexError::InvalidNumber {
+                span: shift(span),
+                literal: literal,
+            },
+            LexError::IntegerOverflow { span, literal } => LexError::IntegerOverflow {
+                span: shift(span),
+                literal: literal,
+            },
+            LexError::InvalidEscape { span, escape } => LexError::InvalidEscape {
+                span: shift(span),
+                escape: escape,
+            },
+            LexError::InvalidCharacter { span, literal } => LexError::InvalidCharacter {
+                span: shift(span),
+                literal: literal,
+            },
+            LexError::UnterminatedString { start } => LexError::UnterminatedString {
+                start: start + offset,
+            },
+            LexError::UnterminatedCharacter { start } => LexError::UnterminatedCharacter {
+                start: start + offset,
+            },
+            LexError::UnterminatedComment { start } => LexError::UnterminatedComment {
+                start: start + offset,
+            },
+            LexError::Read {
+                offset: start,
+                message,
+            } => LexError::Read {
+                offset: start + offset,
+                message: message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::UnknownToken {
+                offset,
+                snippet,
+                suggestion,
+            } => {
+                write!(f, "Unknown Token at byte {}: '{}'", offset, snippet)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean '{}'?", suggestion)?;
+                }
+                Ok(())
+            }
+            LexError::InvalidNumber { span, literal } => {
+                write!(f, "Invalid number at byte {}: '{}'", span.start, literal)
+            }
+            LexError::IntegerOverflow { span, literal } => write!(
+                f,
+                "Integer literal at byte {} does not fit in 64 bits: '{}'",
+                span.start, literal
+            ),
+            LexError::InvalidEscape { span, escape } => {
+                write!(f, "Invalid escape at byte {}: '{}'", span.start, escape)
+            }
+            LexError::InvalidCharacter { span, literal } => {
+                write!(f, "Invalid character at byte {}: {}", span.start, literal)
+            }
+            LexError::UnterminatedString { start } => {
+                write!(f, "Unterminated string starting at byte {}", start)
+            }
+            LexError::UnterminatedCharacter { start } => {
+                write!(f, "Unterminated character starting at byte {}", start)
+            }
+            LexError::UnterminatedComment { start } => {
+                write!(f, "Unterminated comment starting at byte {}", start)
+            }
+            LexError::Read { offset, message } => {
+                write!(f, "Failed to read code at byte {}: {}", offset, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub(crate) message: String,
+    // Byte range of the offending code
+    pub(crate) span: Range<usize>,
+}
+
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> Self {
+        Self {
+            message: error.to_string(),
+            span: error.span(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Found by the type checker before the Program is run
 #[derive(Debug, PartialEq)]
+pub(crate) struct TypeError {
+    pub(crate) message: String,
+    // Byte range of the offending code
+    pub(crate) span: Range<usize>,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum DataType {
+    Array(ArrayType),
+    Boolean,
     Character,
+    Float,
     Integer,
     None,
     String,
+    Void,
 }
 
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            DataType::Array(ArrayType { elem, size: None }) => write!(f, "[{}]", elem),
+            DataType::Array(ArrayType {
+                elem,
+                size: Some(size),
+            }) => write!(f, "[{}; {}]", elem, size),
+            DataType::Boolean => write!(f, "bool"),
             DataType::Character => write!(f, "char"),
+            DataType::Float => write!(f, "float"),
             DataType::Integer => write!(f, "int"),
             DataType::None => write!(f, "None"),
             DataType::String => write!(f, "str"),
+            DataType::Void => write!(f, "void"),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+// Parameters are written like 'a: int', 'b: int = 10' or 'rest: int...'
+// Lambda parameters may leave out the type
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.typ != DataType::None {
+            write!(f, ": {}", self.typ)?;
+        }
+        if self.variadic {
+            write!(f, "...")?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, " = {}", default)?;
+        }
+        Ok(())
+    }
+}
+
+// Type of arrays like '[int]', fixed-size ones like '[int; 5]' also have a size
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ArrayType {
+    pub(crate) elem: Box<DataType>,
+    pub(crate) size: Option<Box<Expr>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Parameter {
     pub(crate) name: String,
     pub(crate) typ: DataType,
+    // Constant used when the argument is left out, e.g. 'b: int = 10'
+    pub(crate) default: Option<Expr>,
+    // Last parameter collecting the remaining arguments into an array, e.g. 'args: int...'
+    pub(crate) variadic: bool,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Program {
-    pub(crate) statements: Vec<Statement>,
+    pub(crate) statements: Vec<Stmt>,
 }
 
-impl fmt::Display for Program {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for statement in &self.statements {
-            writeln!(f, "{}", statement);
-        }
-        Ok(())
-    }
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Stmt {
+    // Serialized next to the span, e.g. {"kind": "Return", "value": ..., "span": ...}
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub(crate) kind: StmtKind,
+    // Byte range from the first to the last Token of the Statement
+    span: Range<usize>,
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Statement {
-    pub(crate) typ: StatementType,
-    pub(crate) value: Option<String>,
-    pub(crate) options: Option<StatementOptions>,
-    pub(crate) expression: Option<Expression>,
-    pub(crate) statements: Option<Vec<Statement>>,
-}
-
-impl Statement {
-    pub(crate) fn new(
-        typ: StatementType,
-        value: Option<String>,
-        options: Option<StatementOptions>,
-        expression: Option<Expression>,
-        statements: Option<Vec<Statement>>,
-    ) -> Self {
+impl Stmt {
+    pub(crate) fn init(kind: StmtKind, span: Range<usize>) -> Self {
         Self {
-            typ: typ,
-            value: value,
-            options: options,
-            expression: expression,
-            statements: statements,
+            kind: kind,
+            span: span,
         }
     }
+
+    pub(crate) fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum StatementType {
-    Compound,
-    Conditional,
-    Expression,
-    Function,
-    Loop,
+// Nodes are compared by kind only, so trees parsed from differently laid out code are equal
+impl PartialEq for Stmt {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
+pub(crate) enum StmtKind {
+    // Statements in curly brackets, their own scope
+    Block(Vec<Stmt>),
+    Break,
+    Continue,
+    EnumDecl(EnumDecl),
+    Expression(Expr),
+    FnDecl(FnDecl),
+    // C-style 'for (init; condition; step)', every clause can be left empty
+    For {
+        init: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        step: Option<Expr>,
+        body: Vec<Stmt>,
+    },
+    // 'for x in 0..10', iterating a range or an array
+    ForIn {
+        variable: String,
+        iterable: Expr,
+        body: Vec<Stmt>,
+    },
+    // 'if' chain, the 'elif' branches are checked in order before 'else'
+    If {
+        condition: Expr,
+        body: Vec<Stmt>,
+        elifs: Vec<(Expr, Vec<Stmt>)>,
+        else_body: Option<Vec<Stmt>>,
+    },
     NoOperation,
-    Return,
-    Variable(DataType),
+    // Plain 'return;' has no value
+    Return(Option<Expr>),
+    StructDecl(StructDecl),
+    VarDecl(VarDecl),
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct FnDecl {
+    pub(crate) name: String,
+    pub(crate) parameters: Vec<Parameter>,
+    // DataType::None when the function does not declare one
+    pub(crate) return_type: DataType,
+    pub(crate) body: Vec<Stmt>,
+    // Text of a '///' or '/** */' comment right before the declaration, if trivia was kept
+    pub(crate) doc: Option<String>,
+}
+
+// Definition like 'enum Color { Red, Green, Blue }'
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct EnumDecl {
+    pub(crate) name: String,
+    pub(crate) variants: Vec<String>,
 }
 
-impl fmt::Display for Statement {
+// Definition like 'struct Point { x: int, y: int }'
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct StructDecl {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) typ: DataType,
+}
+
+impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self.typ {
-            StatementType::Compound => {
-                write!(f, "{}\n", "{").unwrap();
-                for statement in self.statements.as_ref().unwrap() {
-                    write!(f, "  {}\n", statement).unwrap();
+        write!(f, "{}: {}", self.name, self.typ)
+    }
+}
+
+// Declaration like 'let x: int = 1;', 'const y = 2;' or 'int z = 3;'
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct VarDecl {
+    pub(crate) name: String,
+    // DataType::None when the type is left to be inferred
+    pub(crate) typ: DataType,
+    pub(crate) value: Option<Expr>,
+    // Only 'const' declarations are immutable
+    pub(crate) mutable: bool,
+    pub(crate) doc: Option<String>,
+}
+
+// Statements on a single line, e.g. 'if a { return; } else { b; }'
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            StmtKind::Block(body) => write_body(f, body),
+            StmtKind::Break => write!(f, "break;"),
+            StmtKind::Continue => write!(f, "continue;"),
+            StmtKind::EnumDecl(declaration) => match declaration.variants.is_empty() {
+                true => write!(f, "enum {} {{}}", declaration.name),
+                false => {
+                    let variants: String = declaration.variants.join(", ");
+                    write!(f, "enum {} {{ {} }}", declaration.name, variants)
+                }
+            },
+            StmtKind::Expression(expression) => write!(f, "{};", expression),
+            StmtKind::FnDecl(function) => {
+                write!(f, "fun {}(", function.name)?;
+                write_parameters(f, &function.parameters)?;
+                write!(f, ") ")?;
+                if function.return_type != DataType::None {
+                    write!(f, "-> {} ", function.return_type)?;
                 }
-                write!(f, "{}", "}")
+                write_body(f, &function.body)
             }
-            StatementType::Conditional => {
-                let typ: &String = self.value.as_ref().unwrap();
-                let expression: &Expression = self.expression.as_ref().unwrap();
-                write!(
-                    f,
-                    "{} ({}) {}",
-                    typ,
-                    expression,
-                    self.statements.as_ref().unwrap()[0]
-                )
+            StmtKind::For {
+                init,
+                condition,
+                step,
+                body,
+            } => {
+                write!(f, "{} ", get_for_header(init, condition, step))?;
+                write_body(f, body)
             }
-            StatementType::Expression => {
-                write!(f, "{};", self.expression.as_ref().unwrap())
+            StmtKind::ForIn {
+                variable,
+                iterable,
+                body,
+            } => {
+                write!(f, "for {} in {} ", variable, iterable)?;
+                write_body(f, body)
             }
-            StatementType::Function => {
-                let name: &String = self.value.as_ref().unwrap();
-                write!(f, "fun {} {}", name, self.statements.as_ref().unwrap()[0])
+            StmtKind::If {
+                condition,
+                body,
+                elifs,
+                else_body,
+            } => {
+                write!(f, "if {} ", condition)?;
+                write_body(f, body)?;
+                for (condition, body) in elifs.iter() {
+                    write!(f, " elif {} ", condition)?;
+                    write_body(f, body)?;
+                }
+                if let Some(body) = else_body {
+                    write!(f, " else ")?;
+                    write_body(f, body)?;
+                }
+                Ok(())
             }
-            StatementType::Loop => {
-                let typ: &String = self.value.as_ref().unwrap();
-                let expression: &Expression = self.expression.as_ref().unwrap();
-                write!(
-                    f,
-                    "{} ({}) {}",
-                    typ,
-                    expression,
-                    self.statements.as_ref().unwrap()[0]
-                )
+            StmtKind::NoOperation => write!(f, ";"),
+            StmtKind::Return(None) => write!(f, "return;"),
+            StmtKind::Return(Some(value)) => write!(f, "return {};", value),
+            StmtKind::StructDecl(declaration) => {
+                let fields: Vec<String> = declaration.fields.iter().map(Field::to_string).collect();
+                match fields.is_empty() {
+                    true => write!(f, "struct {} {{}}", declaration.name),
+                    false => write!(f, "struct {} {{ {} }}", declaration.name, fields.join(", ")),
+                }
             }
-            StatementType::NoOperation => write!(f, "NoOp"),
-            StatementType::Return => {
-                write!(f, "return {};", self.expression.as_ref().unwrap())
+            StmtKind::VarDecl(declaration) => {
+                let keyword: &str = if declaration.mutable { "let" } else { "const" };
+                write!(f, "{} {}", keyword, declaration.name)?;
+                if declaration.typ != DataType::None {
+                    write!(f, ": {}", declaration.typ)?;
+                }
+                if let Some(value) = &declaration.value {
+                    write!(f, " = {}", value)?;
+                }
+                write!(f, ";")
             }
-            StatementType::Variable(typ) => {
-                let name: &String = self.value.as_ref().unwrap();
-                write!(
-                    f,
-                    "{} {} = {};",
-                    typ,
-                    name,
-                    self.expression.as_ref().unwrap()
-                )
+            StmtKind::While { condition, body } => {
+                write!(f, "while {} ", condition)?;
+                write_body(f, body)
             }
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct StatementOptions {
-    pub(crate) parameters: Vec<Parameter>,
-    pub(crate) return_type: DataType,
+// Header of a C-style for loop like 'for (let i = 0; (i < n); (i++))' or 'for (;;)'
+pub(crate) fn get_for_header(
+    init: &Option<Box<Stmt>>,
+    condition: &Option<Expr>,
+    step: &Option<Expr>,
+) -> String {
+    let init: String = init
+        .as_ref()
+        .map_or(";".to_string(), |init| init.to_string());
+    let condition: String = condition
+        .as_ref()
+        .map_or(String::new(), |c| format!(" {}", c));
+    let step: String = step
+        .as_ref()
+        .map_or(String::new(), |step| format!(" {}", step));
+    return format!("for ({}{};{})", init, condition, step);
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct Expression {
-    pub(crate) typ: ExpressionType,
-    pub(crate) value: Option<String>,
-    pub(crate) expressions: Option<Vec<Expression>>,
+fn write_body(f: &mut fmt::Formatter, body: &[Stmt]) -> fmt::Result {
+    if body.is_empty() {
+        return write!(f, "{{}}");
+    }
+    write!(f, "{{")?;
+    for statement in body.iter() {
+        write!(f, " {}", statement)?;
+    }
+    return write!(f, " }}");
+}
+
+fn write_parameters(f: &mut fmt::Formatter, parameters: &[Parameter]) -> fmt::Result {
+    let parameters: Vec<String> = parameters.iter().map(Parameter::to_string).collect();
+    return write!(f, "{}", parameters.join(", "));
+}
+
+// Which side operators of the same precedence are grouped from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Expr {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub(crate) kind: ExprKind,
+    // Byte range from the first to the last Token of the Expression
+    span: Range<usize>,
 }
 
-impl Expression {
-    pub(crate) fn new(
-        typ: ExpressionType,
-        value: Option<String>,
-        expressions: Option<Vec<Expression>>,
-    ) -> Self {
+impl Expr {
+    pub(crate) fn init(kind: ExprKind, span: Range<usize>) -> Self {
         Self {
-            typ: typ,
-            value: value,
-            expressions: expressions,
+            kind: kind,
+            span: span,
         }
     }
+
+    pub(crate) fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub(crate) enum ExpressionType {
-    Binary,
-    Enclosure,
-    FunctionCall,
-    Identifier,
-    Indexing,
-    Literal(DataType),
-    Unary,
+// Like Statements, Expressions are compared without their spans
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
+pub(crate) enum ExprKind {
+    // Value of the literal Token, e.g. the decimal value of an integer
+    Literal {
+        typ: DataType,
+        value: String,
+    },
+    Identifier(String),
+    Unary {
+        operator: String,
+        operand: Box<Expr>,
+    },
+    // Increments and decrements, the prefix ones evaluate to the updated value
+    PreInc(Box<Expr>),
+    PreDec(Box<Expr>),
+    PostInc(Box<Expr>),
+    PostDec(Box<Expr>),
+    Binary {
+        operator: String,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    // 'condition ? a : b', only the taken branch is evaluated
+    Ternary {
+        condition: Box<Expr>,
+        then_value: Box<Expr>,
+        else_value: Box<Expr>,
+    },
+    // Expression in round brackets
+    Grouping(Box<Expr>),
+    Call {
+        callee: Box<Expr>,
+        arguments: Vec<Expr>,
+    },
+    ArrayLit(Vec<Expr>),
+    // Element access like 'a[i]'
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    // Instance of a struct like 'Point { x: 1, y: 2 }', fields in the order written
+    StructLit {
+        name: String,
+        fields: Vec<(String, Expr)>,
+    },
+    // Field access like 'p.x'
+    FieldAccess {
+        target: Box<Expr>,
+        field: String,
+    },
+    // Variant of an enum like 'Color::Red'
+    EnumVariant {
+        name: String,
+        variant: String,
+    },
+    // 'match x { 1 => a, _ => { b; } }', the first arm whose pattern matches is taken
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    // Anonymous function like '|a: int, b: int| -> int { a + b }' or '|| 42'
+    Lambda {
+        parameters: Vec<Parameter>,
+        // DataType::None when the lambda does not declare one
+        return_type: DataType,
+        body: LambdaBody,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MatchArm {
+    pub(crate) pattern: Pattern,
+    pub(crate) body: ArmBody,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Pattern {
+    // '_' matches every value
+    Wildcard,
+    // Literals and enum variants match values equal to them
+    Value(Expr),
 }
 
-impl fmt::Display for Expression {
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum ArmBody {
+    Block(Vec<Stmt>),
+    Expression(Expr),
+}
+
+impl fmt::Display for MatchArm {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self.typ {
-            ExpressionType::Binary => {
-                let operator: &String = self.value.as_ref().unwrap();
-                let lhs: &String = self.expressions.as_ref().unwrap()[0]
-                    .value
-                    .as_ref()
-                    .unwrap();
-                if self.expressions.as_ref().unwrap()[1].expressions.is_none() {
-                    write!(
-                        f,
-                        "{} {} {}",
-                        lhs,
-                        operator,
-                        self.expressions.as_ref().unwrap()[1]
-                            .value
-                            .as_ref()
-                            .unwrap()
-                    )
-                    .unwrap();
-                } else {
-                    write!(
-                        f,
-                        "{} {} {}",
-                        lhs,
-                        operator,
-                        self.expressions.as_ref().unwrap()[1]
-                    )
-                    .unwrap();
+        match &self.pattern {
+            Pattern::Wildcard => write!(f, "_ => ")?,
+            Pattern::Value(value) => write!(f, "{} => ", value)?,
+        }
+        match &self.body {
+            ArmBody::Block(body) => write_body(f, body),
+            ArmBody::Expression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+// A block body's last Expression without ';' is parsed as its return value
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum LambdaBody {
+    Block(Vec<Stmt>),
+    Expression(Box<Expr>),
+}
+
+impl fmt::Display for ExprKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Operations are fully parenthesized to show how the Expression was nested
+        match self {
+            ExprKind::Literal {
+                typ: DataType::Character,
+                value,
+            } => write!(f, "{:?}", value.chars().next().unwrap_or_default()),
+            ExprKind::Literal {
+                typ: DataType::String,
+                value,
+            } => write!(f, "{:?}", value),
+            ExprKind::Literal { value, .. } => write!(f, "{}", value),
+            ExprKind::Identifier(name) => write!(f, "{}", name),
+            ExprKind::Unary { operator, operand } => write!(f, "({}{})", operator, operand),
+            ExprKind::PreInc(operand) => write!(f, "(++{})", operand),
+            ExprKind::PreDec(operand) => write!(f, "(--{})", operand),
+            ExprKind::PostInc(operand) => write!(f, "({}++)", operand),
+            ExprKind::PostDec(operand) => write!(f, "({}--)", operand),
+            ExprKind::Binary {
+                operator,
+                left,
+                right,
+            } => write!(f, "({} {} {})", left, operator, right),
+            ExprKind::Ternary {
+                condition,
+                then_value,
+                else_value,
+            } => write!(f, "({} ? {} : {})", condition, then_value, else_value),
+            ExprKind::Grouping(expression) => write!(f, "({})", expression),
+            ExprKind::Call { callee, arguments } => {
+                let arguments: Vec<String> = arguments.iter().map(Expr::to_string).collect();
+                write!(f, "{}({})", callee, arguments.join(", "))
+            }
+            ExprKind::ArrayLit(elements) => {
+                let elements: Vec<String> = elements.iter().map(Expr::to_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            ExprKind::Index { target, index } => write!(f, "{}[{}]", target, index),
+            ExprKind::StructLit { name, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            ExprKind::FieldAccess { target, field } => write!(f, "{}.{}", target, field),
+            ExprKind::EnumVariant { name, variant } => write!(f, "{}::{}", name, variant),
+            ExprKind::Match { scrutinee, arms } => {
+                let arms: Vec<String> = arms.iter().map(MatchArm::to_string).collect();
+                match arms.is_empty() {
+                    true => write!(f, "match {} {{}}", scrutinee),
+                    false => write!(f, "match {} {{ {} }}", scrutinee, arms.join(", ")),
                 }
-                Ok(())
             }
-            ExpressionType::Enclosure => todo!(),
-            ExpressionType::FunctionCall => {
-                let name: &String = self.value.as_ref().unwrap();
-                write!(f, "{}(", name).unwrap();
-                for (index, expression) in self.expressions.as_ref().unwrap().iter().enumerate() {
-                    if index > 0 {
-                        write!(f, ", ").unwrap()
-                    }
-                    write!(f, "{}", expression).unwrap()
+            ExprKind::Lambda {
+                parameters,
+                return_type,
+                body,
+            } => {
+                write!(f, "|")?;
+                write_parameters(f, parameters)?;
+                write!(f, "| ")?;
+                if *return_type != DataType::None {
+                    write!(f, "-> {} ", return_type)?;
+                }
+                match body {
+                    LambdaBody::Block(body) => write_body(f, body),
+                    LambdaBody::Expression(expression) => write!(f, "{}", expression),
                 }
-                write!(f, ")")
             }
-            ExpressionType::Identifier => write!(f, "{}", self.value.as_ref().unwrap()),
-            ExpressionType::Indexing => todo!(),
-            ExpressionType::Literal(typ) => {
-                let value: &String = self.value.as_ref().unwrap();
-                write!(f, "{}", value)
+        }
+    }
+}
+
+// Result of evaluating an Expression
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Array(Vec<Value>),
+    Boolean(bool),
+    Character(char),
+    Float(f64),
+    Function(Rc<Closure>),
+    Integer(i64),
+    // Value of 'none'
+    None,
+    String(String),
+    Void,
+}
+
+impl Value {
+    // Name of the type as written in code, used in error messages
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Value::Array(_) => "array",
+            Value::Boolean(_) => "bool",
+            Value::Character(_) => "char",
+            Value::Float(_) => "float",
+            Value::Function(_) => "function",
+            Value::Integer(_) => "int",
+            Value::None => "none",
+            Value::String(_) => "str",
+            Value::Void => "void",
+        }
+    }
+}
+
+// Values are written like print shows them, strings and characters inside arrays are quoted
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Array(elements) => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|element| match element {
+                        Value::Character(value) => format!("'{}'", value),
+                        Value::String(value) => format!("\"{}\"", value),
+                        element => element.to_string(),
+                    })
+                    .collect();
+                write!(f, "[{}]", elements.join(", "))
             }
-            ExpressionType::Unary => {
-                let operator: &String = self.value.as_ref().unwrap();
-                // TODO: Parsing Expression value
-                let value: &String = self.expressions.as_ref().unwrap()[0]
-                    .value
-                    .as_ref()
-                    .unwrap();
-                write!(f, "{}{}", operator, value)
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Character(value) => write!(f, "{}", value),
+            // Debug formatting keeps the '.0' of whole floats
+            Value::Float(value) => write!(f, "{:?}", value),
+            Value::Function(closure) => {
+                write!(f, "<lambda with {} parameters>", closure.parameters.len())
             }
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::None => write!(f, "none"),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Void => write!(f, "void"),
         }
     }
 }
+
+// Lambda together with the variables it captured by value when it was evaluated
+#[derive(Debug, PartialEq)]
+pub(crate) struct Closure {
+    pub(crate) parameters: Vec<Parameter>,
+    pub(crate) body: LambdaBody,
+    pub(crate) captured: HashMap<String, Value>,
+}
+
+// How the execution continues after a Statement
+#[derive(Debug, PartialEq)]
+pub(crate) enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum RuntimeError {
+    // Integer division or remainder by zero
+    DivisionByZero {
+        span: Range<usize>,
+    },
+    // Array index below zero or past the last element
+    IndexOutOfBounds {
+        span: Range<usize>,
+        index: i64,
+        length: usize,
+    },
+    // Call with a different number of arguments than the Function has parameters
+    ArityMismatch {
+        span: Range<usize>,
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    // Writing the output of print failed
+    Output {
+        span: Range<usize>,
+        message: String,
+    },
+    // Integer arithmetic with a result that does not fit in an int
+    Overflow {
+        span: Range<usize>,
+    },
+    // Operation on values it is not defined for, e.g. 'true + 1'
+    TypeError {
+        span: Range<usize>,
+        message: String,
+    },
+    // Variable that is not defined in any enclosing scope
+    Undefined {
+        span: Range<usize>,
+        name: String,
+    },
+    // Assignment to a variable declared with 'const'
+    Immutable {
+        span: Range<usize>,
+        name: String,
+    },
+    // Code the interpreter can not run yet, e.g. an Expression or a Statement's keyword
+    Unsupported {
+        span: Range<usize>,
+        code: String,
+    },
+}
+
+impl RuntimeError {
+    pub(crate) fn span(&self) -> Range<usize> {
+        match self {
+            RuntimeError::DivisionByZero { span } => span.clone(),
+            RuntimeError::IndexOutOfBounds { span, .. } => span.clone(),
+            RuntimeError::ArityMismatch { span, .. } => span.clone(),
+            RuntimeError::Output { span, .. } => span.clone(),
+            RuntimeError::Overflow { span } => span.clone(),
+            RuntimeError::TypeError { span, .. } => span.clone(),
+            RuntimeError::Undefined { span, .. } => span.clone(),
+            RuntimeError::Immutable { span, .. } => span.clone(),
+            RuntimeError::Unsupported { span, .. } => span.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::DivisionByZero { span } => {
+                write!(f, "Division by zero at byte {}", span.start)
+            }
+            RuntimeError::IndexOutOfBounds {
+                span,
+                index,
+                length,
+            } => write!(
+                f,
+                "Index {} out of bounds for length {} at byte {}",
+                index, length, span.start
+            ),
+            RuntimeError::ArityMismatch {
+                span,
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function '{}' expects {} arguments but found {} at byte {}",
+                name, expected, found, span.start
+            ),
+            RuntimeError::Output { span, message } => {
+                write!(
+                    f,
+                    "Failed to write output at byte {}: {}",
+                    span.start, message
+                )
+            }
+            RuntimeError::Overflow { span } => {
+                write!(f, "Integer overflow at byte {}", span.start)
+            }
+            RuntimeError::TypeError { span, message } => {
+                write!(f, "Type error at byte {}: {}", span.start, message)
+            }
+            RuntimeError::Undefined { span, name } => {
+                write!(f, "Undefined variable at byte {}: '{}'", span.start, name)
+            }
+            RuntimeError::Immutable { span, name } => {
+                write!(
+                    f,
+                    "Assignment to constant at byte {}: '{}'",
+                    span.start, name
+                )
+            }
+            RuntimeError::Unsupported { span, code } => {
+                write!(f, "Unsupported code at byte {}: '{}'", span.start, code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
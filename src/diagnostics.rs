@@ -0,0 +1,63 @@
+use std::ops::Range;
+
+// Render an error message with the offending line of code and carets under the span, e.g.
+// Unknown Token at byte 4: '@;'
+// line 1: a = @;
+//             ^
+pub(crate) fn render_diagnostic(code: &str, message: &str, span: Range<usize>) -> String {
+    let start: usize = span.start.min(code.len());
+    let line_start: usize = code[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end: usize = code[start..]
+        .find('\n')
+        .map_or(code.len(), |index| start + index);
+    let line_number: usize = code[..line_start].matches('\n').count() + 1;
+    let line: &str = code[line_start..line_end].trim_end_matches('\r');
+
+    // Carets cover the span on its first line, and at least one character
+    let prefix: String = format!("line {}: ", line_number);
+    let column: usize = code[line_start..start].chars().count();
+    let end: usize = span.end.clamp(start, line_start + line.len());
+    let width: usize = code[start..end].chars().count().max(1);
+    return format!(
+        "{}\n{}{}\n{}{}",
+        message,
+        prefix,
+        line,
+        " ".repeat(prefix.len() + column),
+        "^".repeat(width)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs::LexError;
+    use crate::lexer::Parser;
+
+    #[test]
+    fn test_rendering_lex_error() {
+        let code: &str = "int a = 1;\nint b = §;\n";
+        let mut parser: Parser = Parser::init(code);
+        let error: LexError = parser.parse().unwrap_err();
+        assert_eq!(
+            render_diagnostic(code, &error.to_string(), error.span()),
+            "Unknown Token at byte 19: '§;'\nline 2: int b = §;\n                ^"
+        );
+    }
+
+    #[test]
+    fn test_rendering_span() {
+        let code: &str = "x = 1e;";
+        assert_eq!(
+            render_diagnostic(code, "Invalid number", 4..6),
+            "Invalid number\nline 1: x = 1e;\n            ^^"
+        );
+
+        // Spans over multiple lines are only marked on the first one
+        let code: &str = "a /* b\nc";
+        assert_eq!(
+            render_diagnostic(code, "Unterminated comment", 2..8),
+            "Unterminated comment\nline 1: a /* b\n          ^^^^"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+// Strings of the identifiers and keywords interned so far, shared by every Tokenizer so equal
+// ones get the same Symbol. Strings are only ever appended, so resolving a Symbol never takes
+// a lock. They are kept until the program exits, which only names are interned for, so the
+// table grows with the distinct names in the code rather than with its length
+static STRINGS: boxcar::Vec<Box<str>> = boxcar::Vec::new();
+
+// Symbol of each interned string, only locked to intern one
+static SYMBOLS: Lazy<Mutex<HashMap<&'static str, Symbol>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Interned string, comparing Symbols is as cheap as comparing integers
+// The integer is the index of the string in STRINGS
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Symbol(u32);
+
+impl Symbol {
+    pub(crate) fn as_str(&self) -> &'static str {
+        return resolve(*self);
+    }
+}
+
+// Get the Symbol of the string, interning it if it has not been seen before
+pub(crate) fn intern(string: &str) -> Symbol {
+    let mut symbols = SYMBOLS.lock().expect("Interner is never poisoned");
+    if let Some(symbol) = symbols.get(string) {
+        return *symbol;
+    }
+    let index: usize = STRINGS.push(string.into());
+    let symbol: Symbol = Symbol(u32::try_from(index).expect("Too many interned strings"));
+    symbols.insert(resolve(symbol), symbol);
+    return symbol;
+}
+
+pub(crate) fn resolve(symbol: Symbol) -> &'static str {
+    return &STRINGS[symbol.0 as usize];
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs::{Lexeme, Token};
+    use crate::lexer::Parser;
+
+    fn get_symbol(token: &Token) -> Option<Symbol> {
+        match token.lexeme {
+            Lexeme::Symbol(symbol) => Some(symbol),
+            Lexeme::Text(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_interning_lexemes() {
+        let mut parser: Parser = Parser::init("let count = count + other; let");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let symbols: Vec<Option<Symbol>> = tokens.iter().map(get_symbol).collect();
+
+        // Equal names share a Symbol and resolve to the same string
+        assert_eq!(symbols[1], symbols[3]);
+        assert_eq!(symbols[0], symbols[7]);
+        assert_eq!(symbols[1].map(resolve), Some("count"));
+        assert_eq!(Some(intern("count")), symbols[1]);
+
+        assert_ne!(symbols[1], symbols[5]);
+        assert_eq!(symbols[5].map(resolve), Some("other"));
+        assert_ne!(Some(intern("Count")), symbols[1]);
+
+        // A Symbol is only the index of its string
+        assert_eq!(std::mem::size_of::<Symbol>(), 4);
+    }
+
+    #[test]
+    fn test_interning_only_names() {
+        let mut parser: Parser = Parser::init("x = \"lit\" + 'c' + 42; // note").with_trivia(true);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let interned: Vec<&str> = tokens
+            .iter()
+            .filter(|token| get_symbol(token).is_some())
+            .map(|token| token.lexeme.as_str())
+            .collect();
+        assert_eq!(interned, vec!["x"]);
+
+        // Literals, operators and trivia keep their own text
+        assert_eq!(tokens[4].lexeme, Lexeme::Text("\"lit\"".to_string()));
+        assert_eq!(tokens[tokens.len() - 1].lexeme, "// note");
+    }
+}
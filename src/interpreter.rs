@@ -0,0 +1,1460 @@
+use crate::ast::ASSIGNMENT_OPERATORS;
+use crate::defs::{
+    Closure, DataType, Expr, ExprKind, Flow, FnDecl, LambdaBody, Parameter, Program, RuntimeError,
+    Stmt, StmtKind, Value, VarDecl,
+};
+use crate::utils::get_arity;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+
+// Variable's value and whether it was declared with 'let' instead of 'const'
+pub(crate) struct Binding {
+    pub(crate) value: Value,
+    pub(crate) mutable: bool,
+}
+
+// Variables visible to the code being evaluated, innermost scope last
+// Functions are global no matter where they are declared
+pub(crate) struct Env {
+    scopes: Vec<HashMap<String, Binding>>,
+    functions: HashMap<String, Rc<FnDecl>>,
+    output: Box<dyn Write>,
+}
+
+impl Env {
+    // Starts with the global scope which is never popped
+    pub(crate) fn init() -> Self {
+        return Self::with_output(Box::new(io::stdout()));
+    }
+
+    // Built-in print functions write to the output instead of stdout
+    pub(crate) fn with_output(output: Box<dyn Write>) -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            output: output,
+        }
+    }
+
+    // Function bodies only see the global scope, not the variables of their caller
+    fn enter_function(&mut self) -> Vec<HashMap<String, Binding>> {
+        let caller_scopes: Vec<HashMap<String, Binding>> = self.scopes.split_off(1);
+        self.push_scope();
+        return caller_scopes;
+    }
+
+    fn leave_function(&mut self, caller_scopes: Vec<HashMap<String, Binding>>) {
+        self.scopes.truncate(1);
+        self.scopes.extend(caller_scopes);
+    }
+
+    pub(crate) fn define_function(&mut self, function: &FnDecl) {
+        self.functions
+            .insert(function.name.clone(), Rc::new(function.clone()));
+    }
+
+    pub(crate) fn get_function(&self, name: &str) -> Option<Rc<FnDecl>> {
+        return self.functions.get(name).cloned();
+    }
+
+    pub(crate) fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub(crate) fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    // Defining a name again in the same scope replaces the old Binding
+    pub(crate) fn define(&mut self, name: &str, value: Value, mutable: bool) {
+        let binding: Binding = Binding {
+            value: value,
+            mutable: mutable,
+        };
+        self.scopes
+            .last_mut()
+            .expect("Env always has the global scope")
+            .insert(name.to_string(), binding);
+    }
+
+    // Inner scopes shadow the outer ones
+    pub(crate) fn get(&self, name: &str) -> Option<&Value> {
+        return self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .map(|binding| &binding.value);
+    }
+
+    // Variables of every scope but the global one, which lambdas always see
+    pub(crate) fn capture(&self) -> HashMap<String, Value> {
+        let mut captured: HashMap<String, Value> = HashMap::new();
+        for scope in self.scopes.iter().skip(1) {
+            for (name, binding) in scope.iter() {
+                captured.insert(name.clone(), binding.value.clone());
+            }
+        }
+        return captured;
+    }
+
+    pub(crate) fn get_binding_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        return self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name));
+    }
+}
+
+// Run the statements of the Program one after another until one of them fails
+pub(crate) fn exec_program(program: &Program, env: &mut Env) -> Result<(), RuntimeError> {
+    return program
+        .statements
+        .iter()
+        .try_for_each(|statement| exec_stmt(statement, env).map(|_| ()));
+}
+
+pub(crate) fn exec_stmt(statement: &Stmt, env: &mut Env) -> Result<Flow, RuntimeError> {
+    match &statement.kind {
+        StmtKind::Block(body) => exec_block(body, env),
+        StmtKind::Break => Ok(Flow::Break),
+        StmtKind::Continue => Ok(Flow::Continue),
+        StmtKind::Expression(expression) => {
+            eval_expr(expression, env)?;
+            Ok(Flow::Normal)
+        }
+        StmtKind::FnDecl(function) => {
+            env.define_function(function);
+            Ok(Flow::Normal)
+        }
+        // Definitions only matter to struct literals and enum variants, which have no Value yet
+        StmtKind::EnumDecl(_) | StmtKind::StructDecl(_) => Ok(Flow::Normal),
+        StmtKind::If {
+            condition,
+            body,
+            elifs,
+            else_body,
+        } => {
+            // The first branch whose condition is true is executed
+            let branches = [(condition, body)]
+                .into_iter()
+                .chain(elifs.iter().map(|(condition, body)| (condition, body)));
+            for (condition, body) in branches {
+                if eval_condition(condition, "if", env)? {
+                    return exec_block(body, env);
+                }
+            }
+            match else_body {
+                Some(body) => exec_block(body, env),
+                None => Ok(Flow::Normal),
+            }
+        }
+        StmtKind::NoOperation => Ok(Flow::Normal),
+        StmtKind::Return(None) => Ok(Flow::Return(Value::Void)),
+        StmtKind::Return(Some(value)) => Ok(Flow::Return(eval_expr(value, env)?)),
+        StmtKind::VarDecl(declaration) => {
+            exec_declaration(declaration, env)?;
+            Ok(Flow::Normal)
+        }
+        // The initializer gets a scope around the whole loop
+        StmtKind::For {
+            init,
+            condition,
+            step,
+            body,
+        } => {
+            env.push_scope();
+            let result: Result<Flow, RuntimeError> = exec_for(init, condition, step, body, env);
+            env.pop_scope();
+            result
+        }
+        StmtKind::ForIn {
+            variable,
+            iterable,
+            body,
+        } => {
+            // Ranges are iterated lazily so their length does not matter
+            match eval_iterable(iterable, env)? {
+                Iterable::Range(range) => {
+                    exec_for_in(variable, range.map(Value::Integer), body, env)
+                }
+                Iterable::InclusiveRange(range) => {
+                    exec_for_in(variable, range.map(Value::Integer), body, env)
+                }
+                Iterable::Array(values) => exec_for_in(variable, values.into_iter(), body, env),
+            }
+        }
+        StmtKind::While { condition, body } => {
+            while eval_condition(condition, "while", env)? {
+                match exec_block(body, env)? {
+                    Flow::Break => break,
+                    Flow::Return(value) => return Ok(Flow::Return(value)),
+                    Flow::Normal | Flow::Continue => continue,
+                }
+            }
+            Ok(Flow::Normal)
+        }
+    }
+}
+
+// Loops without a condition run until 'break' or 'return', 'continue' still runs the step
+fn exec_for(
+    init: &Option<Box<Stmt>>,
+    condition: &Option<Expr>,
+    step: &Option<Expr>,
+    body: &[Stmt],
+    env: &mut Env,
+) -> Result<Flow, RuntimeError> {
+    if let Some(init) = init {
+        exec_stmt(init, env)?;
+    }
+    loop {
+        if let Some(condition) = condition {
+            if !eval_condition(condition, "for", env)? {
+                break;
+            }
+        }
+        match exec_block(body, env)? {
+            Flow::Break => break,
+            Flow::Return(value) => return Ok(Flow::Return(value)),
+            Flow::Normal | Flow::Continue => {}
+        }
+        if let Some(step) = step {
+            eval_expr(step, env)?;
+        }
+    }
+    return Ok(Flow::Normal);
+}
+
+fn exec_for_in(
+    variable: &str,
+    values: impl Iterator<Item = Value>,
+    body: &[Stmt],
+    env: &mut Env,
+) -> Result<Flow, RuntimeError> {
+    for value in values {
+        // Each value gets a fresh scope so the body can not keep the variable
+        env.push_scope();
+        env.define(variable, value, true);
+        let flow: Result<Flow, RuntimeError> = exec_block(body, env);
+        env.pop_scope();
+        match flow? {
+            Flow::Break => break,
+            Flow::Return(value) => return Ok(Flow::Return(value)),
+            Flow::Normal | Flow::Continue => continue,
+        }
+    }
+    return Ok(Flow::Normal);
+}
+
+// Values a 'for' loop can iterate over
+enum Iterable {
+    Range(Range<i64>),
+    InclusiveRange(RangeInclusive<i64>),
+    Array(Vec<Value>),
+}
+
+// Ranges of integers can only be iterated, the other iterables are arrays
+fn eval_iterable(iterable: &Expr, env: &mut Env) -> Result<Iterable, RuntimeError> {
+    if let ExprKind::Binary {
+        operator,
+        left,
+        right,
+    } = &iterable.kind
+    {
+        if operator.starts_with("..") {
+            let start: Value = eval_expr(left, env)?;
+            let end: Value = eval_expr(right, env)?;
+            let (Value::Integer(start), Value::Integer(end)) = (&start, &end) else {
+                return Err(RuntimeError::TypeError {
+                    span: iterable.span(),
+                    message: format!(
+                        "Operator '{}' can not be applied to {} and {}",
+                        operator,
+                        start.type_name(),
+                        end.type_name()
+                    ),
+                });
+            };
+            return match operator.as_str() {
+                "..=" => Ok(Iterable::InclusiveRange(*start..=*end)),
+                _ => Ok(Iterable::Range(*start..*end)),
+            };
+        }
+    }
+    match eval_expr(iterable, env)? {
+        Value::Array(values) => Ok(Iterable::Array(values)),
+        value => Err(RuntimeError::TypeError {
+            span: iterable.span(),
+            message: format!("Can not iterate over {}", value.type_name()),
+        }),
+    }
+}
+
+// Variables declared inside the block are dropped at its end, even after an error
+// 'break', 'continue' and 'return' stop the block and are passed on to the enclosing loop or Function
+fn exec_block(body: &[Stmt], env: &mut Env) -> Result<Flow, RuntimeError> {
+    env.push_scope();
+    let mut result: Result<Flow, RuntimeError> = Ok(Flow::Normal);
+    for statement in body.iter() {
+        result = exec_stmt(statement, env);
+        if result != Ok(Flow::Normal) {
+            break;
+        }
+    }
+    env.pop_scope();
+    return result;
+}
+
+// Declarations without a value hold void until they are assigned
+fn exec_declaration(declaration: &VarDecl, env: &mut Env) -> Result<(), RuntimeError> {
+    let value: Value = match &declaration.value {
+        Some(value) => eval_expr(value, env)?,
+        None => Value::Void,
+    };
+    env.define(&declaration.name, value, declaration.mutable);
+    return Ok(());
+}
+
+fn eval_condition(condition: &Expr, keyword: &str, env: &mut Env) -> Result<bool, RuntimeError> {
+    match eval_expr(condition, env)? {
+        Value::Boolean(value) => Ok(value),
+        value => Err(RuntimeError::TypeError {
+            span: condition.span(),
+            message: format!(
+                "Condition of '{}' must be bool but found {}",
+                keyword,
+                value.type_name()
+            ),
+        }),
+    }
+}
+
+pub(crate) fn eval_expr(expression: &Expr, env: &mut Env) -> Result<Value, RuntimeError> {
+    let span: Range<usize> = expression.span();
+    match &expression.kind {
+        ExprKind::Literal { typ, value } => get_literal_value(typ, value, span),
+        ExprKind::Identifier(name) => match env.get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError::Undefined {
+                span: span,
+                name: name.clone(),
+            }),
+        },
+        ExprKind::Unary { operator, operand } => {
+            let operand: Value = eval_expr(operand, env)?;
+            eval_unary(operator, operand, span)
+        }
+        ExprKind::PreInc(operand) => eval_increment(operand, "+", false, span, env),
+        ExprKind::PreDec(operand) => eval_increment(operand, "-", false, span, env),
+        ExprKind::PostInc(operand) => eval_increment(operand, "+", true, span, env),
+        ExprKind::PostDec(operand) => eval_increment(operand, "-", true, span, env),
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } if ASSIGNMENT_OPERATORS.contains(&operator.as_str()) => {
+            eval_assignment(operator, left, right, span, env)
+        }
+        // Ranges, structs and enums have no Value yet, so nothing can be matched against them
+        ExprKind::Binary { operator, .. } if operator.starts_with("..") => {
+            Err(RuntimeError::Unsupported {
+                span: span,
+                code: expression.to_string(),
+            })
+        }
+        ExprKind::StructLit { .. }
+        | ExprKind::FieldAccess { .. }
+        | ExprKind::EnumVariant { .. }
+        | ExprKind::Match { .. } => Err(RuntimeError::Unsupported {
+            span: span,
+            code: expression.to_string(),
+        }),
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } => eval_binary(operator, left, right, span, env),
+        ExprKind::Ternary {
+            condition,
+            then_value,
+            else_value,
+        } => match eval_condition(condition, "?", env)? {
+            true => eval_expr(then_value, env),
+            false => eval_expr(else_value, env),
+        },
+        ExprKind::Grouping(expression) => eval_expr(expression, env),
+        ExprKind::ArrayLit(elements) => {
+            let elements: Result<Vec<Value>, RuntimeError> = elements
+                .iter()
+                .map(|element| eval_expr(element, env))
+                .collect();
+            Ok(Value::Array(elements?))
+        }
+        ExprKind::Call { callee, arguments } => eval_call(callee, arguments, span, env),
+        ExprKind::Lambda {
+            parameters, body, ..
+        } => Ok(Value::Function(Rc::new(Closure {
+            parameters: parameters.clone(),
+            body: body.clone(),
+            captured: env.capture(),
+        }))),
+        ExprKind::Index { target, index } => {
+            let target: Value = eval_expr(target, env)?;
+            let index: Value = eval_expr(index, env)?;
+            eval_index(target, index, span)
+        }
+    }
+}
+
+// Arguments are evaluated in the caller's scopes and bound to the parameters in a fresh one
+// Variables holding a lambda shadow Functions of the same name
+fn eval_call(
+    callee: &Expr,
+    arguments: &[Expr],
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    let name: String = match &callee.kind {
+        ExprKind::Identifier(name) if env.get(name).is_none() => name.clone(),
+        _ => match eval_expr(callee, env)? {
+            Value::Function(closure) => {
+                return call_closure(&closure, &callee.to_string(), arguments, span, env)
+            }
+            _ => {
+                return Err(RuntimeError::TypeError {
+                    span: callee.span(),
+                    message: format!("Can not call '{}'", callee),
+                })
+            }
+        },
+    };
+    let function: Rc<FnDecl> = match env.get_function(&name) {
+        Some(function) => function,
+        // Functions declared in code shadow the built-in ones
+        None if name == "print" || name == "println" => {
+            return eval_print(&name, arguments, span, env)
+        }
+        None => {
+            return Err(RuntimeError::Undefined {
+                span: callee.span(),
+                name: name,
+            })
+        }
+    };
+    let arguments: Vec<Value> = eval_arguments(&name, &function.parameters, arguments, span, env)?;
+
+    let caller_scopes: Vec<HashMap<String, Binding>> = env.enter_function();
+    let flow: Result<Flow, RuntimeError> = bind_parameters(&function.parameters, arguments, env)
+        .and_then(|_| exec_block(&function.body, env));
+    env.leave_function(caller_scopes);
+    match flow? {
+        Flow::Return(value) => Ok(value),
+        _ => Ok(Value::Void),
+    }
+}
+
+// Captured variables are in a scope between the global one and the parameters
+fn call_closure(
+    closure: &Closure,
+    name: &str,
+    arguments: &[Expr],
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    let arguments: Vec<Value> = eval_arguments(name, &closure.parameters, arguments, span, env)?;
+
+    let caller_scopes: Vec<HashMap<String, Binding>> = env.enter_function();
+    for (name, value) in closure.captured.iter() {
+        env.define(name, value.clone(), true);
+    }
+    env.push_scope();
+    let value: Result<Value, RuntimeError> = bind_parameters(&closure.parameters, arguments, env)
+        .and_then(|_| match &closure.body {
+            LambdaBody::Expression(expression) => eval_expr(expression, env),
+            LambdaBody::Block(body) => match exec_block(body, env)? {
+                Flow::Return(value) => Ok(value),
+                _ => Ok(Value::Void),
+            },
+        });
+    env.leave_function(caller_scopes);
+    return value;
+}
+
+fn eval_arguments(
+    name: &str,
+    parameters: &[Parameter],
+    arguments: &[Expr],
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Vec<Value>, RuntimeError> {
+    let (required, maximum): (usize, Option<usize>) = get_arity(parameters);
+    if arguments.len() < required || maximum.is_some_and(|maximum| arguments.len() > maximum) {
+        return Err(RuntimeError::ArityMismatch {
+            span: span,
+            name: name.to_string(),
+            expected: match arguments.len() < required {
+                true => required,
+                false => parameters.len(),
+            },
+            found: arguments.len(),
+        });
+    }
+    return arguments
+        .iter()
+        .map(|argument| eval_expr(argument, env))
+        .collect();
+}
+
+// Variadic parameters take the remaining arguments and left out ones get their default
+fn bind_parameters(
+    parameters: &[Parameter],
+    mut arguments: Vec<Value>,
+    env: &mut Env,
+) -> Result<(), RuntimeError> {
+    for (index, parameter) in parameters.iter().enumerate() {
+        let value: Value = match &parameter.default {
+            _ if parameter.variadic => {
+                Value::Array(arguments.split_off(index.min(arguments.len())))
+            }
+            _ if index < arguments.len() => arguments[index].clone(),
+            Some(default) => eval_expr(default, env)?,
+            None => unreachable!("Arguments for parameters without a default are checked"),
+        };
+        env.define(&parameter.name, value, true);
+    }
+    return Ok(());
+}
+
+// Arguments of any type are written separated by spaces, 'println' also ends the line
+fn eval_print(
+    name: &str,
+    arguments: &[Expr],
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    let arguments: Vec<String> = arguments
+        .iter()
+        .map(|argument| eval_expr(argument, env).map(|value| value.to_string()))
+        .collect::<Result<Vec<String>, RuntimeError>>()?;
+    let mut text: String = arguments.join(" ");
+    if name == "println" {
+        text.push('\n');
+    }
+    match env.output.write_all(text.as_bytes()) {
+        Ok(()) => Ok(Value::Void),
+        Err(error) => Err(RuntimeError::Output {
+            span: span,
+            message: error.to_string(),
+        }),
+    }
+}
+
+// Assignments evaluate to the stored value so they can be chained like a = b = 1
+fn eval_assignment(
+    operator: &str,
+    target: &Expr,
+    value: &Expr,
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    let value: Value = eval_expr(value, env)?;
+    let value: Value = match operator.strip_suffix('=') {
+        Some("") | None => value,
+        Some(operator) => apply_binary(operator, eval_expr(target, env)?, value, span)?,
+    };
+    store(target, value.clone(), env)?;
+    return Ok(value);
+}
+
+// Postfix increments evaluate to the value before the change, prefix ones to the value after it
+fn eval_increment(
+    operand: &Expr,
+    operator: &str,
+    postfix: bool,
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    let old: Value = eval_expr(operand, env)?;
+    let new: Value = apply_binary(operator, old.clone(), Value::Integer(1), span)?;
+    store(operand, new.clone(), env)?;
+    match postfix {
+        true => Ok(old),
+        false => Ok(new),
+    }
+}
+
+// Assign to the nearest Binding of the variable
+fn store(target: &Expr, value: Value, env: &mut Env) -> Result<(), RuntimeError> {
+    let span: Range<usize> = target.span();
+    let name: &String = match &target.kind {
+        ExprKind::Identifier(name) => name,
+        _ => {
+            return Err(RuntimeError::TypeError {
+                span: span,
+                message: format!("Can not assign to '{}'", target),
+            })
+        }
+    };
+    match env.get_binding_mut(name) {
+        Some(binding) if binding.mutable => {
+            binding.value = value;
+            Ok(())
+        }
+        Some(_) => Err(RuntimeError::Immutable {
+            span: span,
+            name: name.clone(),
+        }),
+        None => Err(RuntimeError::Undefined {
+            span: span,
+            name: name.clone(),
+        }),
+    }
+}
+
+fn get_literal_value(
+    typ: &DataType,
+    value: &str,
+    span: Range<usize>,
+) -> Result<Value, RuntimeError> {
+    // Integer literals are already in decimal but float literals are still their lexeme
+    match typ {
+        DataType::Boolean => Ok(Value::Boolean(value == "true")),
+        DataType::Character => Ok(Value::Character(value.chars().next().unwrap_or_default())),
+        DataType::Float => Ok(Value::Float(
+            value
+                .replace('_', "")
+                .parse()
+                .expect("Float literals are validated by the lexer"),
+        )),
+        DataType::Integer => match value.parse() {
+            Ok(value) => Ok(Value::Integer(value)),
+            Err(_) => Err(RuntimeError::Overflow { span: span }),
+        },
+        DataType::String => Ok(Value::String(value.to_string())),
+        DataType::None => Ok(Value::None),
+        DataType::Array(_) | DataType::Void => Ok(Value::Void),
+    }
+}
+
+fn eval_unary(operator: &str, operand: Value, span: Range<usize>) -> Result<Value, RuntimeError> {
+    match (operator, operand) {
+        ("-", Value::Integer(operand)) => match operand.checked_neg() {
+            Some(value) => Ok(Value::Integer(value)),
+            None => Err(RuntimeError::Overflow { span: span }),
+        },
+        ("-", Value::Float(operand)) => Ok(Value::Float(-operand)),
+        ("!", Value::Boolean(operand)) => Ok(Value::Boolean(!operand)),
+        ("!", Value::Integer(operand)) => Ok(Value::Integer(!operand)),
+        (operator, operand) => Err(RuntimeError::TypeError {
+            span: span,
+            message: format!(
+                "Operator '{}' can not be applied to {}",
+                operator,
+                operand.type_name()
+            ),
+        }),
+    }
+}
+
+fn eval_binary(
+    operator: &str,
+    left: &Expr,
+    right: &Expr,
+    span: Range<usize>,
+    env: &mut Env,
+) -> Result<Value, RuntimeError> {
+    // Logical operators only evaluate the right side when it decides the result
+    if operator == "&&" || operator == "||" {
+        let left: bool = eval_logical_operand(operator, left, env)?;
+        if left == (operator == "||") {
+            return Ok(Value::Boolean(left));
+        }
+        return Ok(Value::Boolean(eval_logical_operand(operator, right, env)?));
+    }
+    if operator == "??" {
+        return match eval_expr(left, env)? {
+            Value::None => eval_expr(right, env),
+            left => Ok(left),
+        };
+    }
+
+    let left: Value = eval_expr(left, env)?;
+    let right: Value = eval_expr(right, env)?;
+    return apply_binary(operator, left, right, span);
+}
+
+fn apply_binary(
+    operator: &str,
+    left: Value,
+    right: Value,
+    span: Range<usize>,
+) -> Result<Value, RuntimeError> {
+    let value: Option<Value> = match (&left, &right) {
+        (Value::Integer(left), Value::Integer(right)) => {
+            eval_integer_binary(operator, *left, *right, &span)?
+        }
+        (Value::Float(left), Value::Float(right)) => eval_float_binary(operator, *left, *right),
+        (Value::Boolean(left), Value::Boolean(right)) => {
+            eval_boolean_binary(operator, *left, *right)
+        }
+        (Value::String(left), Value::String(right)) => eval_string_binary(operator, left, right),
+        _ => None,
+    };
+    return value.ok_or_else(|| RuntimeError::TypeError {
+        span: span,
+        message: format!(
+            "Operator '{}' can not be applied to {} and {}",
+            operator,
+            left.type_name(),
+            right.type_name()
+        ),
+    });
+}
+
+fn eval_logical_operand(
+    operator: &str,
+    operand: &Expr,
+    env: &mut Env,
+) -> Result<bool, RuntimeError> {
+    match eval_expr(operand, env)? {
+        Value::Boolean(value) => Ok(value),
+        value => Err(RuntimeError::TypeError {
+            span: operand.span(),
+            message: format!(
+                "Operator '{}' expects bool but found {}",
+                operator,
+                value.type_name()
+            ),
+        }),
+    }
+}
+
+// Arithmetic is checked so overflows are errors instead of wrapping around
+fn eval_integer_binary(
+    operator: &str,
+    left: i64,
+    right: i64,
+    span: &Range<usize>,
+) -> Result<Option<Value>, RuntimeError> {
+    let overflow = || RuntimeError::Overflow { span: span.clone() };
+    let value: Value = match operator {
+        "+" => Value::Integer(left.checked_add(right).ok_or_else(overflow)?),
+        "-" => Value::Integer(left.checked_sub(right).ok_or_else(overflow)?),
+        "*" => Value::Integer(left.checked_mul(right).ok_or_else(overflow)?),
+        "/" | "%" if right == 0 => return Err(RuntimeError::DivisionByZero { span: span.clone() }),
+        // Division truncates towards zero and the remainder takes the sign of the left side
+        "/" => Value::Integer(left.checked_div(right).ok_or_else(overflow)?),
+        "%" => Value::Integer(left.checked_rem(right).ok_or_else(overflow)?),
+        "**" if right < 0 => {
+            return Err(RuntimeError::TypeError {
+                span: span.clone(),
+                message: format!("Integer exponent can not be negative, found {}", right),
+            })
+        }
+        "**" => {
+            let exponent: Option<u32> = u32::try_from(right).ok();
+            let power: Option<i64> = exponent.and_then(|exponent| left.checked_pow(exponent));
+            Value::Integer(power.ok_or_else(overflow)?)
+        }
+        "&" => Value::Integer(left & right),
+        "|" => Value::Integer(left | right),
+        "^" => Value::Integer(left ^ right),
+        "<<" | ">>" => {
+            let shift: Option<u32> = u32::try_from(right).ok();
+            let shifted: Option<i64> = match operator {
+                "<<" => shift.and_then(|shift| left.checked_shl(shift)),
+                _ => shift.and_then(|shift| left.checked_shr(shift)),
+            };
+            Value::Integer(shifted.ok_or_else(overflow)?)
+        }
+        "==" => Value::Boolean(left == right),
+        "!=" => Value::Boolean(left != right),
+        "<" => Value::Boolean(left < right),
+        "<=" => Value::Boolean(left <= right),
+        ">" => Value::Boolean(left > right),
+        ">=" => Value::Boolean(left >= right),
+        _ => return Ok(None),
+    };
+    return Ok(Some(value));
+}
+
+fn eval_float_binary(operator: &str, left: f64, right: f64) -> Option<Value> {
+    let value: Value = match operator {
+        "+" => Value::Float(left + right),
+        "-" => Value::Float(left - right),
+        "*" => Value::Float(left * right),
+        "/" => Value::Float(left / right),
+        "%" => Value::Float(left % right),
+        "**" => Value::Float(left.powf(right)),
+        "==" => Value::Boolean(left == right),
+        "!=" => Value::Boolean(left != right),
+        "<" => Value::Boolean(left < right),
+        "<=" => Value::Boolean(left <= right),
+        ">" => Value::Boolean(left > right),
+        ">=" => Value::Boolean(left >= right),
+        _ => return None,
+    };
+    return Some(value);
+}
+
+fn eval_boolean_binary(operator: &str, left: bool, right: bool) -> Option<Value> {
+    let value: bool = match operator {
+        "&" => left & right,
+        "|" => left | right,
+        "^" => left ^ right,
+        "==" => left == right,
+        "!=" => left != right,
+        _ => return None,
+    };
+    return Some(Value::Boolean(value));
+}
+
+// Strings are compared by content
+fn eval_string_binary(operator: &str, left: &str, right: &str) -> Option<Value> {
+    let value: Value = match operator {
+        "+" => Value::String(format!("{}{}", left, right)),
+        "==" => Value::Boolean(left == right),
+        "!=" => Value::Boolean(left != right),
+        _ => return None,
+    };
+    return Some(value);
+}
+
+fn eval_index(target: Value, index: Value, span: Range<usize>) -> Result<Value, RuntimeError> {
+    let (elements, index): (Vec<Value>, i64) = match (target, index) {
+        (Value::Array(elements), Value::Integer(index)) => (elements, index),
+        (target, index) => {
+            return Err(RuntimeError::TypeError {
+                span: span,
+                message: format!(
+                    "Can not index {} with {}",
+                    target.type_name(),
+                    index.type_name()
+                ),
+            })
+        }
+    };
+    let length: usize = elements.len();
+    match usize::try_from(index)
+        .ok()
+        .and_then(|index| elements.get(index))
+    {
+        Some(element) => Ok(element.clone()),
+        None => Err(RuntimeError::IndexOutOfBounds {
+            span: span,
+            index: index,
+            length: length,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{generate_ast, parse_expression};
+    use crate::defs::Program;
+    use crate::lexer::{Parser, TokenStream};
+    use std::cell::RefCell;
+
+    // Output that can still be read after it was moved into an Env
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            return self.0.borrow_mut().write(buffer);
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    fn eval_from(code: &str) -> Result<Value, RuntimeError> {
+        let mut parser: Parser = Parser::init(code);
+        let mut tokens: TokenStream = TokenStream::init(parser.tokens());
+        let expression: Expr = parse_expression(&mut tokens).unwrap();
+        return eval_expr(&expression, &mut Env::init());
+    }
+
+    fn run_from(code: &str) -> (Env, Result<(), RuntimeError>) {
+        return run_with_output(code, SharedBuffer::default());
+    }
+
+    fn run_with_output(code: &str, output: SharedBuffer) -> (Env, Result<(), RuntimeError>) {
+        let mut parser: Parser = Parser::init(code);
+        let program: Program = generate_ast(&parser.parse().unwrap()).unwrap();
+        let mut env: Env = Env::with_output(Box::new(output));
+        let result: Result<(), RuntimeError> = exec_program(&program, &mut env);
+        return (env, result);
+    }
+
+    #[test]
+    fn test_eval_integer_expression() {
+        assert_eq!(eval_from("1 + 2 * 3"), Ok(Value::Integer(7)));
+        assert_eq!(eval_from("10 / 3"), Ok(Value::Integer(3)));
+
+        let expressions: Vec<(&str, i64)> = vec![
+            ("(1 + 2) * 3", 9),
+            ("-7 / 2", -3),
+            ("-7 % 3", -1),
+            ("7 % -3", 1),
+            ("2 ** 3 ** 2", 512),
+            ("-2 ** 2", -4),
+            ("0xff & 0b1010 | 1 ^ 4", 15),
+            ("1 << 4 >> 2", 4),
+            ("!0", -1),
+            ("1_000 - 0o10", 992),
+        ];
+        for (code, value) in expressions {
+            assert_eq!(eval_from(code), Ok(Value::Integer(value)), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_eval_float_expression() {
+        assert_eq!(eval_from("1.5 * 2.0 - 0.5"), Ok(Value::Float(2.5)));
+        assert_eq!(eval_from("7.0 / 2.0"), Ok(Value::Float(3.5)));
+        assert_eq!(eval_from("2.0 ** 0.5 > 1.41"), Ok(Value::Boolean(true)));
+        assert_eq!(eval_from("1_0.0e-1"), Ok(Value::Float(1.0)));
+        assert_eq!(eval_from("-0.5"), Ok(Value::Float(-0.5)));
+    }
+
+    #[test]
+    fn test_eval_boolean_expression() {
+        let expressions: Vec<(&str, bool)> = vec![
+            ("true && false", false),
+            ("true || false", true),
+            ("!true", false),
+            ("1 < 2 && 2 <= 2 && 3 > 2 && 3 >= 4", false),
+            ("1 == 1 != false", true),
+            ("true ^ true", false),
+            // The right side is not evaluated when the left side decides the result
+            ("false && 1 / 0 == 0", false),
+            ("true || undefined", true),
+        ];
+        for (code, value) in expressions {
+            assert_eq!(eval_from(code), Ok(Value::Boolean(value)), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_short_circuit_evaluation() {
+        // 'calls' counts how often the right side was evaluated
+        let code: &str = "let calls = 0; fun check(v: bool) -> bool { calls++; return v; }
+            let a = false && check(true); let b = true || check(false);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("calls"), Some(&Value::Integer(0)));
+        assert_eq!(env.get("a"), Some(&Value::Boolean(false)));
+        assert_eq!(env.get("b"), Some(&Value::Boolean(true)));
+
+        let code: &str = "let calls = 0; fun check(v: bool) -> bool { calls++; return v; }
+            let a = true && check(false); let b = false || check(true);
+            let c = check(false) && check(true) || check(true);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("calls"), Some(&Value::Integer(4)));
+        assert_eq!(env.get("a"), Some(&Value::Boolean(false)));
+        assert_eq!(env.get("b"), Some(&Value::Boolean(true)));
+        assert_eq!(env.get("c"), Some(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn test_eval_string_expression() {
+        assert_eq!(
+            eval_from("\"a\" + \"b\" == \"ab\""),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_from("\"pog\" + \"\" + \"-lang\""),
+            Ok(Value::String("pog-lang".to_string()))
+        );
+        assert_eq!(eval_from("\"a\" != \"a\""), Ok(Value::Boolean(false)));
+        assert_eq!(
+            eval_from("\"ab\" == \"a\" + \"b\""),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_from("\"a\" + 1"),
+            Err(RuntimeError::TypeError {
+                span: 0..7,
+                message: "Operator '+' can not be applied to str and int".to_string(),
+            })
+        );
+        assert_eq!(
+            eval_from("\"a\" - \"b\"").unwrap_err().to_string(),
+            "Type error at byte 0: Operator '-' can not be applied to str and str"
+        );
+    }
+
+    #[test]
+    fn test_eval_array_expression() {
+        assert_eq!(eval_from("[1, 2 + 3, 4][1]"), Ok(Value::Integer(5)));
+        assert_eq!(
+            eval_from("[[true], []]"),
+            Ok(Value::Array(vec![
+                Value::Array(vec![Value::Boolean(true)]),
+                Value::Array(vec![]),
+            ]))
+        );
+        assert_eq!(
+            eval_from("[1][1]"),
+            Err(RuntimeError::IndexOutOfBounds {
+                span: 0..6,
+                index: 1,
+                length: 1,
+            })
+        );
+        assert_eq!(
+            eval_from("[1][-1]").unwrap_err().to_string(),
+            "Index -1 out of bounds for length 1 at byte 0"
+        );
+    }
+
+    #[test]
+    fn test_eval_runtime_errors() {
+        assert_eq!(
+            eval_from("1 + 10 % 0"),
+            Err(RuntimeError::DivisionByZero { span: 4..10 })
+        );
+        assert_eq!(
+            eval_from("9223372036854775807 + 1"),
+            Err(RuntimeError::Overflow { span: 0..23 })
+        );
+        assert_eq!(eval_from("2 ** 64").unwrap_err().span(), 0..7);
+        assert_eq!(
+            eval_from("1 + 1.0"),
+            Err(RuntimeError::TypeError {
+                span: 0..7,
+                message: "Operator '+' can not be applied to int and float".to_string(),
+            })
+        );
+        assert_eq!(
+            eval_from("1 && true").unwrap_err().to_string(),
+            "Type error at byte 0: Operator '&&' expects bool but found int"
+        );
+        assert_eq!(
+            eval_from("-true").unwrap_err().to_string(),
+            "Type error at byte 0: Operator '-' can not be applied to bool"
+        );
+        assert_eq!(
+            eval_from("x"),
+            Err(RuntimeError::Undefined {
+                span: 0..1,
+                name: "x".to_string(),
+            })
+        );
+        assert_eq!(
+            eval_from("1..2").unwrap_err().to_string(),
+            "Unsupported code at byte 0: '(1 .. 2)'"
+        );
+    }
+
+    #[test]
+    fn test_scope_shadowing() {
+        let code: &str = "let x = 1; let y = 0; { let x = 2; x += 10; y = x; } let z = x++;";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(2)));
+        assert_eq!(env.get("y"), Some(&Value::Integer(12)));
+        assert_eq!(env.get("z"), Some(&Value::Integer(1)));
+
+        // Assignment targets the nearest Binding and block variables are dropped at its end
+        let (env, result): (Env, Result<(), RuntimeError>) =
+            run_from("let a = 1; { { a = ++a * 3; let b = a; } }");
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("a"), Some(&Value::Integer(6)));
+        assert_eq!(env.get("b"), None);
+    }
+
+    #[test]
+    fn test_scope_errors() {
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("{ let x = 1; } x;");
+        assert_eq!(
+            result,
+            Err(RuntimeError::Undefined {
+                span: 15..16,
+                name: "x".to_string(),
+            })
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("y = 1;");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Undefined variable at byte 0: 'y'"
+        );
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from("const c = 1; c += 1;");
+        assert_eq!(
+            result,
+            Err(RuntimeError::Immutable {
+                span: 13..14,
+                name: "c".to_string(),
+            })
+        );
+        assert_eq!(env.get("c"), Some(&Value::Integer(1)));
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("let a = 1; 1 = a;");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 11: Can not assign to '1'"
+        );
+    }
+
+    #[test]
+    fn test_while_loop() {
+        let code: &str = "let i = 0; let sum = 0; while i < 10 { i += 1; sum += i; }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("i"), Some(&Value::Integer(10)));
+        assert_eq!(env.get("sum"), Some(&Value::Integer(55)));
+
+        // Only odd numbers are added and the loop stops at 7
+        let code: &str = "let i = 0; let sum = 0;
+            while true { i++; if i % 2 == 0 { continue; } elif i > 7 { break; } sum += i; }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("i"), Some(&Value::Integer(9)));
+        assert_eq!(env.get("sum"), Some(&Value::Integer(16)));
+
+        // 'break' only stops the innermost loop
+        let code: &str = "let n = 0; let i = 0;
+            while i < 3 { i++; let j = 0; while true { j++; n++; if j == 2 { break; } } }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("n"), Some(&Value::Integer(6)));
+    }
+
+    #[test]
+    fn test_for_loop() {
+        // The loop variable is only visible inside the loop
+        let code: &str = "let sum = 0; let i = 100; for (let i = 0; i < 5; i++) { sum += i; }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("sum"), Some(&Value::Integer(10)));
+        assert_eq!(env.get("i"), Some(&Value::Integer(100)));
+
+        // 'continue' still runs the step and loops without a condition end with 'break'
+        let code: &str = "let n = 0; let odd = 0;
+            for (let i = 0;; i++) { if i == 7 { break; } n++; if i % 2 == 0 { continue; } odd++; }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("n"), Some(&Value::Integer(7)));
+        assert_eq!(env.get("odd"), Some(&Value::Integer(3)));
+
+        let code: &str = "fun first() -> int { for (;;) { return 4; } } let x = first();";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(4)));
+
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from("for (let i = 0; i; i++) {}");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 16: Condition of 'for' must be bool but found int"
+        );
+        assert_eq!(env.get("i"), None);
+    }
+
+    #[test]
+    fn test_ternary() {
+        assert_eq!(eval_from("1 < 2 ? 10 : 20"), Ok(Value::Integer(10)));
+        assert_eq!(eval_from("false ? 1 : true ? 2 : 3"), Ok(Value::Integer(2)));
+
+        // Only the taken branch is evaluated
+        let code: &str = "let calls = 0; fun f(v: int) -> int { calls++; return v; }
+            let a = true ? f(1) : f(2); let b = false ? f(3) : 4; let c = true ? 5 : 1 / 0;";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("calls"), Some(&Value::Integer(1)));
+        assert_eq!(env.get("a"), Some(&Value::Integer(1)));
+        assert_eq!(env.get("b"), Some(&Value::Integer(4)));
+        assert_eq!(env.get("c"), Some(&Value::Integer(5)));
+
+        assert_eq!(
+            eval_from("1 ? 2 : 3").unwrap_err().to_string(),
+            "Type error at byte 0: Condition of '?' must be bool but found int"
+        );
+    }
+
+    #[test]
+    fn test_null_coalescing() {
+        assert_eq!(eval_from("none ?? 2"), Ok(Value::Integer(2)));
+        assert_eq!(eval_from("1 ?? 2"), Ok(Value::Integer(1)));
+        assert_eq!(
+            eval_from("none ?? none ?? \"c\""),
+            Ok(Value::String("c".to_string()))
+        );
+        assert_eq!(eval_from("none"), Ok(Value::None));
+
+        // The right side is only evaluated when the left one is none
+        let code: &str = "let calls = 0; fun f() -> int { calls++; return 0; }
+            let a = 1 ?? f(); let b = false ?? 1 / 0; let c = none ?? f();";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("calls"), Some(&Value::Integer(1)));
+        assert_eq!(env.get("a"), Some(&Value::Integer(1)));
+        assert_eq!(env.get("b"), Some(&Value::Boolean(false)));
+        assert_eq!(env.get("c"), Some(&Value::Integer(0)));
+    }
+
+    #[test]
+    fn test_for_in_loop() {
+        let code: &str = "let sum = 0; for x in 0..5 { sum += x; }";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("sum"), Some(&Value::Integer(10)));
+        assert_eq!(env.get("x"), None);
+
+        let loops: Vec<(&str, i64)> = vec![
+            ("let n = 0; for x in 1..=4 { n += x; }", 10),
+            ("let n = 0; for x in 3..3 { n++; }", 0),
+            ("let n = 0; for c in ['a', 'b'] { n++; }", 2),
+            (
+                "let n = 0; for x in [1, 2, 3, 4] { if x == 3 { break; } n += x; }",
+                3,
+            ),
+            (
+                "let n = 0; for x in 0..4 { if x % 2 == 0 { continue; } n += x; }",
+                4,
+            ),
+            // Ranges are not collected before the loop starts
+            (
+                "let n = 0; for x in 0..9223372036854775807 { n = x; if x == 2 { break; } }",
+                2,
+            ),
+            (
+                "let n = 0; for x in 0..=9223372036854775807 { n++; break; }",
+                1,
+            ),
+        ];
+        for (code, n) in loops {
+            let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+            assert_eq!(result, Ok(()), "{}", code);
+            assert_eq!(env.get("n"), Some(&Value::Integer(n)), "{}", code);
+        }
+
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("for x in 5 {}");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 9: Can not iterate over int"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("for x in 0..1.5 {}");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 9: Operator '..' can not be applied to int and float"
+        );
+    }
+
+    #[test]
+    fn test_if_statement() {
+        let branches: Vec<(i64, &str)> = vec![(1, "one"), (2, "two"), (3, "many")];
+        for (x, branch) in branches {
+            let code: String = format!(
+                "let x = {}; let branch = \"\";
+                if x == 1 {{ branch = \"one\"; }} elif x == 2 {{ branch = \"two\"; }}
+                else {{ branch = \"many\"; }}",
+                x
+            );
+            let (env, result): (Env, Result<(), RuntimeError>) = run_from(&code);
+            assert_eq!(result, Ok(()));
+            assert_eq!(
+                env.get("branch"),
+                Some(&Value::String(branch.to_string())),
+                "{}",
+                x
+            );
+        }
+
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("if 1 { }");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 3: Condition of 'if' must be bool but found int"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("while [] { }");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 6: Condition of 'while' must be bool but found array"
+        );
+    }
+
+    #[test]
+    fn test_function_call() {
+        let code: &str =
+            "fun fact(n: int) -> int { if n <= 1 { return 1; } return n * fact(n - 1); }
+            let result = fact(5); let correct = fact(5) == 120;";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("result"), Some(&Value::Integer(120)));
+        assert_eq!(env.get("correct"), Some(&Value::Boolean(true)));
+
+        // Functions without 'return' give void and can only see global variables
+        let code: &str =
+            "let a = 1; fun set() { a = 2; let b = 3; } { let b = 0; let v = set(); b++; }
+            fun first(x: int, y: int) -> int { while true { return x; } return y; }
+            let f = first(4, 5);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("a"), Some(&Value::Integer(2)));
+        assert_eq!(env.get("f"), Some(&Value::Integer(4)));
+
+        let (_, result): (Env, Result<(), RuntimeError>) =
+            run_from("let x = 1; fun f() -> int { return x + y; } { let y = 1; f(); }");
+        assert_eq!(
+            result,
+            Err(RuntimeError::Undefined {
+                span: 39..40,
+                name: "y".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_parameters() {
+        let code: &str =
+            "fun add(a: int, b: int = 10, c: int = 2 * 50) -> int { return a + b + c; }
+            let x = add(1); let y = add(1, 2); let z = add(1, 2, 3);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(111)));
+        assert_eq!(env.get("y"), Some(&Value::Integer(103)));
+        assert_eq!(env.get("z"), Some(&Value::Integer(6)));
+
+        let code: &str = "fun f(a: int, b: int = 1) {} f();";
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Function 'f' expects 1 arguments but found 0 at byte 29"
+        );
+        let code: &str = "fun f(a: int, b: int = 1) {} f(1, 2, 3);";
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Function 'f' expects 2 arguments but found 3 at byte 29"
+        );
+    }
+
+    #[test]
+    fn test_variadic_parameters() {
+        let code: &str =
+            "fun last(rest: int...) -> int { return rest[2]; } let x = last(4, 5, 6, 7);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(6)));
+
+        // Variadic parameters without arguments are empty arrays
+        let code: &str = "fun f(a: int, rest: int...) -> int { return rest[0]; } f(1);";
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Index 0 out of bounds for length 0 at byte 44"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) =
+            run_from("fun f(a: int, b: int...) {} f();");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Function 'f' expects 1 arguments but found 0 at byte 28"
+        );
+
+        // Defaults before the variadic parameter can leave it with no arguments at all
+        let (_, result): (Env, Result<(), RuntimeError>) =
+            run_from("fun f(a: int = 0, rest: int...) {} f();");
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_function_call_errors() {
+        let (_, result): (Env, Result<(), RuntimeError>) =
+            run_from("fun add(a: int, b: int) -> int { return a + b; } add(1);");
+        assert_eq!(
+            result,
+            Err(RuntimeError::ArityMismatch {
+                span: 49..55,
+                name: "add".to_string(),
+                expected: 2,
+                found: 1,
+            })
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) =
+            run_from("fun add(a: int, b: int) -> int { return a + b; } add(1);");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Function 'add' expects 2 arguments but found 1 at byte 49"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("missing();");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Undefined variable at byte 0: 'missing'"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("[1](2);");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 0: Can not call '[1]'"
+        );
+    }
+
+    #[test]
+    fn test_lambda_call() {
+        let code: &str = "let add = |a: int, b: int| -> int { a + b }; let x = add(2, 3);
+            let y = (|| 42)(); let twice = |f, v| f(f(v)); let z = twice(|n| n * 3, 2);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(5)));
+        assert_eq!(env.get("y"), Some(&Value::Integer(42)));
+        assert_eq!(env.get("z"), Some(&Value::Integer(18)));
+
+        // Lambdas capture local variables when created and always see global ones
+        let code: &str = "let g = 1; let f = 0; { let l = 10; f = |x| x + l + g; l = 100; }
+            g = 5; let x = f(1);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("x"), Some(&Value::Integer(16)));
+
+        // Lambdas returned from a function keep its locals after it returns
+        let code: &str =
+            "fun make() { let n = 5; return |x| x + n; } let f = make(); let y = f(1);";
+        let (env, result): (Env, Result<(), RuntimeError>) = run_from(code);
+        assert_eq!(result, Ok(()));
+        assert_eq!(env.get("y"), Some(&Value::Integer(6)));
+
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("let f = |a| a; f();");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Function 'f' expects 1 arguments but found 0 at byte 15"
+        );
+        let (_, result): (Env, Result<(), RuntimeError>) = run_from("let n = 1; n();");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Type error at byte 11: Can not call 'n'"
+        );
+    }
+
+    fn output_from(code: &str) -> String {
+        let output: SharedBuffer = SharedBuffer::default();
+        let (_, result): (Env, Result<(), RuntimeError>) = run_with_output(code, output.clone());
+        assert_eq!(result, Ok(()), "{}", code);
+        let bytes: Vec<u8> = output.0.borrow().clone();
+        return String::from_utf8(bytes).unwrap();
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!(output_from("print(\"hello\");"), "hello");
+
+        let prints: Vec<(&str, &str)> = vec![
+            ("println(1, -2.5, 3.0); print(true);", "1 -2.5 3.0\ntrue"),
+            ("print('c', \"str\"); print();", "c str"),
+            (
+                "println([1, [2.0], ['a'], [\"b\"]], [], println());",
+                "\n[1, [2.0], ['a'], [\"b\"]] [] void\n",
+            ),
+            ("fun print(x: int) { println(x * 2); } print(21);", "42\n"),
+        ];
+        for (code, output) in prints {
+            assert_eq!(output_from(code), output, "{}", code);
+        }
+    }
+}
@@ -1,115 +1,1803 @@
-use crate::defs::{TokenType, TOKEN_REGEXES};
-use regex::{Captures, Match, Regex};
+use crate::defs::{
+    DataType, Edit, LexError, Lexeme, ParseError, Token, TokenType, COMPILED_TOKEN_REGEXES,
+    MISTYPED_OPERATORS,
+};
+use crate::intern::{intern, Symbol};
+use crate::utils::{get_integer_value, get_suggestion, unescape};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use regex::{Captures, Match};
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+use std::num::IntErrorKind;
+use std::ops::Range;
+
+// Maximum amount of characters shown from the code in a LexError
+const SNIPPET_LENGTH: usize = 20;
+
+// TokenType, byte range and literal value of a Token found at the cursor
+type TokenMatch = (TokenType, Range<usize>, Option<String>);
+
+// Suffixes giving a number literal its DataType
+// 'u' is kept for unsigned integers, but lexing it is an error until there is such a DataType
+const NUMBER_SUFFIXES: [char; 3] = ['f', 'i', 'u'];
+
+// Minimum number of bytes lexed together as one chunk, extended to the next line break that
+// is not inside a string or comment
+#[cfg(feature = "parallel")]
+const CHUNK_LENGTH: usize = 1 << 16;
 
 #[derive(Debug)]
 pub(crate) struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
 }
 
-impl<'a> Parser<'a> {
-    pub(crate) fn init(code: &'a str) -> Self {
-        Self {
-            tokenizer: Tokenizer::init(code),
+impl<'a> Parser<'a> {
+    pub(crate) fn init(code: &'a str) -> Self {
+        Self {
+            tokenizer: Tokenizer::init(code),
+        }
+    }
+
+    // Keep whitespace and comments as Tokens of TokenType::None, e.g. for formatters
+    #[allow(dead_code)]
+    pub(crate) fn with_trivia(mut self, preserve_trivia: bool) -> Self {
+        self.tokenizer.preserve_trivia = preserve_trivia;
+        return self;
+    }
+
+    pub(crate) fn parse(&mut self) -> Result<Vec<Token>, LexError> {
+        self.tokens().collect()
+    }
+
+    // Lex all of the code, collecting every error instead of stopping at the first one
+    pub(crate) fn parse_recover(&mut self) -> (Vec<Token>, Vec<LexError>) {
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut errors: Vec<LexError> = Vec::new();
+        loop {
+            match self.tokenizer.get_next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => break,
+                Err(error) => {
+                    self.tokenizer.skip_error(&error);
+                    errors.push(error);
+                }
+            }
+        }
+        return (tokens, errors);
+    }
+
+    // Lazily lex the Tokens one by one
+    pub(crate) fn tokens(&mut self) -> &mut Tokenizer<'a> {
+        &mut self.tokenizer
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Tokenizer<'a> {
+    code: &'a str,
+    /// Byte offset into `code`. It is only ever moved to the end of a matched Token,
+    /// so it always lies on a UTF-8 char boundary.
+    cursor: usize,
+    // Line and column of the cursor, both starting from 1
+    line: usize,
+    column: usize,
+    // Emit whitespace and comments instead of skipping them
+    preserve_trivia: bool,
+    // Byte offset of `code` in the whole input, only non-zero when lexing a stream in chunks
+    offset: usize,
+    // Names interned by this Tokenizer, so the shared interner is only locked for new ones
+    symbols: HashMap<&'a str, Symbol>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn init(code: &'a str) -> Self {
+        Self {
+            code: code,
+            cursor: 0,
+            line: 1,
+            column: 1,
+            preserve_trivia: false,
+            offset: 0,
+            symbols: HashMap::new(),
+        }
+    }
+
+    fn advance_cursor(&mut self, end: usize) {
+        // Count line breaks from the code the cursor moves over, '\r\n' being a single one
+        for character in self.code[self.cursor..end].chars() {
+            match character {
+                '\n' => {
+                    self.line += 1;
+                    self.column = 1;
+                }
+                '\r' => {}
+                _ => self.column += 1,
+            }
+        }
+        self.cursor = end;
+    }
+
+    fn has_more_tokens(&self) -> bool {
+        self.cursor < self.code.len()
+    }
+
+    fn get_next_token(&mut self) -> Result<Option<Token>, LexError> {
+        // Skip Tokens like whitespace and comments until an actual Token is found
+        while self.has_more_tokens() {
+            let token: Token = self.match_token()?;
+            if token.token_type != TokenType::None || self.preserve_trivia {
+                return Ok(Some(token));
+            }
+        }
+        return Ok(None);
+    }
+
+    // Move the cursor past the code that caused the error so lexing can resume after it
+    fn skip_error(&mut self, error: &LexError) {
+        let end: usize = match error {
+            // Nothing after an unclosed literal or comment can be lexed reliably
+            LexError::UnterminatedString { .. }
+            | LexError::UnterminatedCharacter { .. }
+            | LexError::UnterminatedComment { .. } => self.code.len(),
+            // Skip at least one character and the whole literal containing an invalid part
+            _ => {
+                let unparsed_code: &str = &self.code[self.cursor..];
+                let next_character: usize = unparsed_code.chars().next().map_or(0, char::len_utf8);
+                let literal_length: usize = COMPILED_TOKEN_REGEXES
+                    .iter()
+                    .find_map(|(regex, _)| regex.find(unparsed_code))
+                    .map_or(0, |literal| literal.end());
+                let length: usize = next_character.max(literal_length);
+                error.span().end.max(self.cursor + length)
+            }
+        };
+        self.advance_cursor(end);
+    }
+
+    fn match_token(&mut self) -> Result<Token, LexError> {
+        debug_assert!(self.code.is_char_boundary(self.cursor));
+        let (token_type, span, value): TokenMatch = self.scan_token()?;
+
+        // Move cursor to the end of the parsed Token
+        let (line, column): (usize, usize) = (self.line, self.column);
+        self.advance_cursor(span.end);
+        let lexeme: &'a str = &self.code[span.clone()];
+        let lexeme: Lexeme = match token_type {
+            TokenType::Identifier | TokenType::Keyword => {
+                Lexeme::Symbol(*self.symbols.entry(lexeme).or_insert_with(|| intern(lexeme)))
+            }
+            _ => Lexeme::Text(lexeme.to_string()),
+        };
+        return Ok(Token {
+            token_type: token_type,
+            lexeme: lexeme,
+            span: span,
+            line: line,
+            column: column,
+            value: value,
+        });
+    }
+
+    fn scan_token(&self) -> Result<TokenMatch, LexError> {
+        let unparsed_code: &str = &self.code[self.cursor..];
+        if MISTYPED_OPERATORS
+            .iter()
+            .any(|operator| unparsed_code.starts_with(operator))
+        {
+            return Err(self.unknown_token());
+        }
+
+        // Tokens that a single regex cannot describe are scanned by hand first
+        if let Some(scanned) = self.scan_shebang() {
+            return Ok(scanned);
+        }
+        if let Some(scanned) = self.scan_string()? {
+            return Ok(scanned);
+        }
+        if let Some(scanned) = self.scan_block_comment()? {
+            return Ok(scanned);
+        }
+        return self.match_regex();
+    }
+
+    fn match_regex(&self) -> Result<TokenMatch, LexError> {
+        // Test if the remaining code matches with any Token regex
+        let unparsed_code: &str = &self.code[self.cursor..];
+        for (regex, token_type) in COMPILED_TOKEN_REGEXES.iter() {
+            let captures: Option<Captures> = regex.captures(unparsed_code);
+            if let Some(captures) = captures {
+                // Take match from capture group if it is explicitly specified
+                let token_match: Match = captures.get(1).or(captures.get(0)).unwrap();
+                let span: Range<usize> =
+                    self.cursor + token_match.start()..self.cursor + token_match.end();
+                if let Some(scanned) = self.scan_number_suffix(token_type, &span)? {
+                    return Ok(scanned);
+                }
+                let value: Option<String> = self.get_literal_value(token_type, &span)?;
+                return Ok(((*token_type).clone(), span, value));
+            }
+        }
+
+        // Literal regexes accept any content, so an unmatched quote can only be unclosed
+        if unparsed_code.starts_with('"') {
+            return Err(LexError::UnterminatedString { start: self.cursor });
+        }
+        if unparsed_code.starts_with('\'') {
+            return Err(LexError::UnterminatedCharacter { start: self.cursor });
+        }
+
+        return Err(self.unknown_token());
+    }
+
+    fn unknown_token(&self) -> LexError {
+        // Show the rest of the line after the unknown Token
+        let unparsed_code: &str = &self.code[self.cursor..];
+        let snippet: &str = unparsed_code.lines().next().unwrap_or_default();
+
+        // Suggest a known operator for the run of symbols starting at the unknown Token
+        let is_symbol = |c: char| !c.is_whitespace() && !c.is_alphanumeric() && c != '_';
+        let symbols_length: usize = unparsed_code
+            .find(|c| !is_symbol(c))
+            .unwrap_or(unparsed_code.len());
+        let suggestion: Option<&str> = get_suggestion(&unparsed_code[..symbols_length]);
+        LexError::UnknownToken {
+            offset: self.cursor,
+            snippet: snippet.chars().take(SNIPPET_LENGTH).collect(),
+            suggestion: suggestion.map(str::to_string),
+        }
+    }
+
+    // A first line like '#!/usr/bin/env pog' makes the file executable and is skipped
+    fn scan_shebang(&self) -> Option<TokenMatch> {
+        if self.offset + self.cursor != 0 || !self.code.starts_with("#!") {
+            return None;
+        }
+        let length: usize = self.code.find('\n').unwrap_or(self.code.len());
+        return Some((TokenType::None, 0..length, None));
+    }
+
+    // Strings that a regex cannot find the end of, starting with an optional raw prefix 'r'
+    // Raw strings like r"C:\path" or r#"say "hi""# are taken as is, without unescaping,
+    // and their closing quote has to be followed by as many '#' as the opening one
+    // Triple-quoted strings like """...""" may contain unescaped quotes and line breaks
+    fn scan_string(&self) -> Result<Option<TokenMatch>, LexError> {
+        let unparsed_code: &str = &self.code[self.cursor..];
+        let raw: bool = unparsed_code.starts_with('r');
+        let prefix_length: usize = if raw {
+            let rest: &str = &unparsed_code[1..];
+            1 + rest.len() - rest.trim_start_matches('#').len()
+        } else {
+            0
+        };
+        let hashes: usize = prefix_length.saturating_sub(1);
+
+        let rest: &str = &unparsed_code[prefix_length..];
+        let quote: &str = if rest.starts_with("\"\"\"") {
+            "\"\"\""
+        } else if raw && rest.starts_with('"') {
+            "\""
+        } else {
+            return Ok(None);
+        };
+
+        // Find the closing quote, skipping over escaped characters unless the string is raw
+        let content_start: usize = prefix_length + quote.len();
+        let closing: String = format!("{}{}", quote, "#".repeat(hashes));
+        let mut content_end: usize = content_start;
+        while !unparsed_code[content_end..].starts_with(&closing) {
+            let mut characters = unparsed_code[content_end..].chars();
+            match characters.next() {
+                Some('\\') if !raw => {
+                    content_end += 1 + characters.next().map_or(0, char::len_utf8);
+                }
+                Some(character) => content_end += character.len_utf8(),
+                None => return Err(LexError::UnterminatedString { start: self.cursor }),
+            }
+        }
+
+        let content: Range<usize> = self.cursor + content_start..self.cursor + content_end;
+        let span: Range<usize> = self.cursor..content.end + closing.len();
+        let value: String = if raw {
+            self.code[content].to_string()
+        } else {
+            self.unescape_literal(content)?
+        };
+        return Ok(Some((
+            TokenType::Literal(DataType::String),
+            span,
+            Some(value),
+        )));
+    }
+
+    // Block comments like /* ... */ are skipped as a Token without a TokenType
+    // They can be nested, so the comment only ends once every '/*' has been closed
+    fn scan_block_comment(&self) -> Result<Option<TokenMatch>, LexError> {
+        let unparsed_code: &str = &self.code[self.cursor..];
+        if !unparsed_code.starts_with("/*") {
+            return Ok(None);
+        }
+
+        let mut depth: usize = 0;
+        let mut index: usize = 0;
+        while let Some(character) = unparsed_code[index..].chars().next() {
+            if unparsed_code[index..].starts_with("/*") {
+                depth += 1;
+                index += 2;
+            } else if unparsed_code[index..].starts_with("*/") {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    let span: Range<usize> = self.cursor..self.cursor + index;
+                    return Ok(Some((TokenType::None, span, None)));
+                }
+            } else {
+                index += character.len_utf8();
+            }
+        }
+        Err(LexError::UnterminatedComment { start: self.cursor })
+    }
+
+    fn get_literal_value(
+        &self,
+        token_type: &TokenType,
+        span: &Range<usize>,
+    ) -> Result<Option<String>, LexError> {
+        let literal: &str = &self.code[span.clone()];
+        match token_type {
+            TokenType::Literal(DataType::Float) => {
+                self.validate_number_end(span)?;
+                return Ok(None);
+            }
+            TokenType::Literal(DataType::Integer) => {
+                self.validate_number_end(span)?;
+                return Ok(Some(self.get_integer_literal(span, span)?));
+            }
+            TokenType::Literal(DataType::Character) => {
+                let value: String = self.unescape_literal(span.start + 1..span.end - 1)?;
+                if value.chars().count() != 1 {
+                    return Err(LexError::InvalidCharacter {
+                        span: span.clone(),
+                        literal: literal.to_string(),
+                    });
+                }
+                return Ok(Some(value));
+            }
+            TokenType::Literal(DataType::String) => {
+                return Ok(Some(self.unescape_literal(span.start + 1..span.end - 1)?));
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    fn unescape_literal(&self, content: Range<usize>) -> Result<String, LexError> {
+        // Unescape the content between the quotes
+        let start: usize = content.start;
+        let content: &str = &self.code[content];
+        unescape(content).map_err(|escape| LexError::InvalidEscape {
+            span: start + escape.start..start + escape.end,
+            escape: content[escape].to_string(),
+        })
+    }
+
+    // Number literals like '42i' or '3.14f' directly followed by a type suffix
+    // The suffix is part of the Token and decides its DataType, so '2f' is a float
+    fn scan_number_suffix(
+        &self,
+        token_type: &TokenType,
+        span: &Range<usize>,
+    ) -> Result<Option<TokenMatch>, LexError> {
+        let is_number: bool = matches!(
+            token_type,
+            TokenType::Literal(DataType::Integer | DataType::Float)
+        );
+        let rest: &str = &self.code[span.end..];
+        let suffix: char = match rest.chars().next() {
+            Some(suffix) if is_number && NUMBER_SUFFIXES.contains(&suffix) => suffix,
+            _ => return Ok(None),
+        };
+        if rest[1..].starts_with(is_identifier_char) {
+            return Ok(None);
+        }
+
+        // Only decimal integers can become floats and floats can not become integers
+        let number: &str = &self.code[span.clone()];
+        let suffixed: Range<usize> = span.start..span.end + 1;
+        let is_decimal: bool = !["0x", "0b", "0o"]
+            .iter()
+            .any(|prefix| number.starts_with(prefix));
+        let value: String = match (suffix, token_type) {
+            ('f', TokenType::Literal(DataType::Float)) => number.to_string(),
+            ('f', _) if is_decimal => format!("{}.0", number),
+            ('i', TokenType::Literal(DataType::Integer)) => {
+                self.get_integer_literal(span, &suffixed)?
+            }
+            _ => {
+                return Err(LexError::InvalidNumber {
+                    literal: self.code[suffixed.clone()].to_string(),
+                    span: suffixed,
+                })
+            }
+        };
+        let typ: DataType = match suffix {
+            'f' => DataType::Float,
+            _ => DataType::Integer,
+        };
+        return Ok(Some((TokenType::Literal(typ), suffixed, Some(value))));
+    }
+
+    // Integer literals are stored in decimal regardless of their radix
+    // Values are 64-bit signed integers, so the literal 'span' must fit in i64
+    fn get_integer_literal(
+        &self,
+        digits: &Range<usize>,
+        span: &Range<usize>,
+    ) -> Result<String, LexError> {
+        let literal: String = self.code[span.clone()].to_string();
+        let value: u128 = match get_integer_value(&self.code[digits.clone()]) {
+            Ok(value) => value,
+            // Literals too large even for u128 overflow as well
+            Err(error) if *error.kind() == IntErrorKind::PosOverflow => u128::MAX,
+            Err(_) => {
+                return Err(LexError::InvalidNumber {
+                    span: span.clone(),
+                    literal: literal,
+                })
+            }
+        };
+        if value > i64::MAX as u128 {
+            return Err(LexError::IntegerOverflow {
+                span: span.clone(),
+                literal: literal,
+            });
+        }
+        return Ok(value.to_string());
+    }
+
+    fn validate_number_end(&self, span: &Range<usize>) -> Result<(), LexError> {
+        // Numbers cannot continue directly to an identifier, e.g. '1e' or '12abc'
+        let rest: &str = &self.code[span.end..];
+        if !rest.starts_with(is_identifier_char) {
+            return Ok(());
+        }
+        let mut end: usize = span.end + rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+
+        // Include the sign of an incomplete exponent like '1e+'
+        if self.code[..end].ends_with(['e', 'E']) && self.code[end..].starts_with(['+', '-']) {
+            end += 1;
+        }
+        Err(LexError::InvalidNumber {
+            span: span.start..end,
+            literal: self.code[span.start..end].to_string(),
+        })
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_' || c == '$';
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token: Result<Option<Token>, LexError> = self.get_next_token();
+        if token.is_err() {
+            // Lexing cannot continue past an unknown Token
+            self.cursor = self.code.len();
+        }
+        token.transpose()
+    }
+}
+
+// Lex code read from a stream line by line instead of reading all of it into memory first
+// Only the code not lexed yet is kept, and a Token is taken once code follows it, so reading
+// more could not have made it longer. Spans are relative to the start of the stream
+#[allow(dead_code)]
+pub(crate) struct StreamTokenizer<R: BufRead> {
+    reader: R,
+    // Code read but not lexed yet, starting from byte 'offset' of the stream
+    pending: String,
+    offset: usize,
+    // Line and column of the start of 'pending'
+    line: usize,
+    column: usize,
+    tokens: VecDeque<Token>,
+    // Error returned after the Tokens lexed before it
+    error: Option<LexError>,
+    read_all: bool,
+}
+
+#[allow(dead_code)]
+impl<R: BufRead> StreamTokenizer<R> {
+    pub(crate) fn init(reader: R) -> Self {
+        Self {
+            reader: reader,
+            pending: String::new(),
+            offset: 0,
+            line: 1,
+            column: 1,
+            tokens: VecDeque::new(),
+            error: None,
+            read_all: false,
+        }
+    }
+
+    // Lex the Tokens in the pending code that are complete
+    // Errors are returned once reading more could not fix them, e.g. an unclosed comment
+    fn lex_pending(&mut self) -> Result<(), LexError> {
+        let mut tokenizer: Tokenizer = Tokenizer::init(&self.pending);
+        tokenizer.line = self.line;
+        tokenizer.column = self.column;
+        tokenizer.offset = self.offset;
+        let mut lexed: (usize, usize, usize) = (0, self.line, self.column);
+        let result: Result<(), LexError> = loop {
+            match tokenizer.get_next_token() {
+                Ok(Some(token)) if self.read_all || token.span.end < self.pending.len() => {
+                    let span: Range<usize> =
+                        token.span.start + self.offset..token.span.end + self.offset;
+                    self.tokens.push_back(Token {
+                        span: span,
+                        ..token
+                    });
+                    lexed = (tokenizer.cursor, tokenizer.line, tokenizer.column);
+                }
+                Ok(None) if self.read_all => {
+                    lexed = (tokenizer.cursor, tokenizer.line, tokenizer.column);
+                    break Ok(());
+                }
+                Ok(_) => break Ok(()),
+                Err(error) if self.read_all || !is_incomplete(&error, self.pending.len()) => {
+                    break Err(error.offset_by(self.offset));
+                }
+                Err(_) => break Ok(()),
+            }
+        };
+
+        let (end, line, column): (usize, usize, usize) = lexed;
+        self.pending.drain(..end);
+        self.offset += end;
+        self.line = line;
+        self.column = column;
+        return result;
+    }
+
+    fn read_line(&mut self) -> Result<(), LexError> {
+        match self.reader.read_line(&mut self.pending) {
+            Ok(0) => self.read_all = true,
+            Ok(_) => {}
+            Err(error) => {
+                return Err(LexError::Read {
+                    offset: self.offset + self.pending.len(),
+                    message: error.to_string(),
+                })
+            }
+        }
+        return Ok(());
+    }
+}
+
+// Unclosed literals and comments, or errors at the end of the code lexed so far, could be
+// valid Tokens continuing on the next lines
+fn is_incomplete(error: &LexError, end: usize) -> bool {
+    match error {
+        LexError::UnterminatedString { .. }
+        | LexError::UnterminatedCharacter { .. }
+        | LexError::UnterminatedComment { .. } => true,
+        _ => error.span().end >= end,
+    }
+}
+
+impl<R: BufRead> Iterator for StreamTokenizer<R> {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Some(Ok(token));
+            }
+            if self.error.is_some() {
+                return self.error.take().map(Err);
+            }
+            if self.read_all && self.pending.is_empty() {
+                return None;
+            }
+            if let Err(error) = self.read_line().and_then(|_| self.lex_pending()) {
+                // Lexing cannot continue past an error
+                self.error = Some(error);
+                self.read_all = true;
+                self.pending.clear();
+            }
+        }
+    }
+}
+
+// Lex the code in chunks of whole lines on multiple threads, giving the same result as lexing
+// all of it at once. Strings and block comments continue past a line break, so a quick scan
+// finds the line breaks outside of them first and each chunk is lexed exactly once
+#[cfg(feature = "parallel")]
+#[allow(dead_code)]
+pub(crate) fn lex_parallel(code: &str) -> Result<Vec<Token>, LexError> {
+    return lex_chunks(code, CHUNK_LENGTH);
+}
+
+#[cfg(feature = "parallel")]
+fn lex_chunks(code: &str, chunk_length: usize) -> Result<Vec<Token>, LexError> {
+    let chunks: Vec<(Range<usize>, usize)> = split_lines(code, chunk_length);
+    let lexed: Vec<Result<Vec<Token>, LexError>> = chunks
+        .par_iter()
+        .map(|(range, line)| lex_chunk(code, range.clone(), *line))
+        .collect();
+
+    // The first error in the code is the one lexing all of it at once stops at
+    let lexed: Vec<Vec<Token>> = lexed.into_iter().collect::<Result<_, LexError>>()?;
+    return Ok(lexed.concat());
+}
+
+// What the scan for chunk boundaries is inside of, only the end of a line in code splits
+#[cfg(feature = "parallel")]
+enum ScanState {
+    Code,
+    LineComment,
+    // Number of '/*' not closed yet
+    BlockComment(usize),
+    String,
+    TripleString,
+    // Closing quote followed by as many '#' as the opening one
+    RawString(String),
+}
+
+// Byte ranges of at least 'length' bytes ending after a line break, and the line they start on
+// A line break inside a string or block comment does not end a range
+#[cfg(feature = "parallel")]
+fn split_lines(code: &str, length: usize) -> Vec<(Range<usize>, usize)> {
+    let bytes: &[u8] = code.as_bytes();
+    let mut chunks: Vec<(Range<usize>, usize)> = Vec::new();
+    let (mut start, mut start_line, mut line): (usize, usize, usize) = (0, 1, 1);
+    let mut state: ScanState = ScanState::Code;
+    let mut index: usize = 0;
+    while index < bytes.len() {
+        let rest: &[u8] = &bytes[index..];
+        let step: usize = match &state {
+            ScanState::Code => scan_code(rest, &mut state),
+            ScanState::LineComment if rest[0] == b'\n' => {
+                state = ScanState::Code;
+                1
+            }
+            ScanState::BlockComment(depth) if rest.starts_with(b"/*") => {
+                state = ScanState::BlockComment(depth + 1);
+                2
+            }
+            ScanState::BlockComment(depth) if rest.starts_with(b"*/") => {
+                state = match depth {
+                    1 => ScanState::Code,
+                    _ => ScanState::BlockComment(depth - 1),
+                };
+                2
+            }
+            ScanState::String | ScanState::TripleString if rest[0] == b'\\' => 2,
+            ScanState::String if rest[0] == b'"' => {
+                state = ScanState::Code;
+                1
+            }
+            ScanState::TripleString if rest.starts_with(b"\"\"\"") => {
+                state = ScanState::Code;
+                3
+            }
+            ScanState::RawString(closing) if rest.starts_with(closing.as_bytes()) => {
+                let step: usize = closing.len();
+                state = ScanState::Code;
+                step
+            }
+            _ => 1,
+        };
+        let end: usize = (index + step).min(bytes.len());
+        line += bytes[index..end].iter().filter(|&&b| b == b'\n').count();
+        index = end;
+
+        if matches!(state, ScanState::Code) && bytes[index - 1] == b'\n' && index - start >= length
+        {
+            chunks.push((start..index, start_line));
+            (start, start_line) = (index, line);
+        }
+    }
+    if start < bytes.len() {
+        chunks.push((start..bytes.len(), start_line));
+    }
+    return chunks;
+}
+
+// Bytes making up the next piece of code, entering a string or comment if one starts there
+#[cfg(feature = "parallel")]
+fn scan_code(rest: &[u8], state: &mut ScanState) -> usize {
+    let is_word = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_';
+    if rest.starts_with(b"//") || rest[0] == b'#' {
+        *state = ScanState::LineComment;
+        return 1;
+    }
+    if rest.starts_with(b"/*") {
+        *state = ScanState::BlockComment(1);
+        return 2;
+    }
+    if rest.starts_with(b"\"\"\"") {
+        *state = ScanState::TripleString;
+        return 3;
+    }
+    if rest[0] == b'"' {
+        *state = ScanState::String;
+        return 1;
+    }
+    // Characters never continue past a line break, but may contain a quote
+    if rest[0] == b'\'' {
+        let mut end: usize = 1;
+        while end < rest.len() && rest[end] != b'\n' {
+            match rest[end] {
+                b'\'' => return end + 1,
+                b'\\' if rest.get(end + 1).is_some_and(|&b| b != b'\n') => end += 2,
+                _ => end += 1,
+            }
+        }
+        return 1;
+    }
+    if !is_word(&rest[0]) {
+        return 1;
+    }
+
+    // Whole words, so only an 'r' starting one can be the prefix of a raw string
+    let word: usize = rest.iter().take_while(|b| is_word(b)).count();
+    if &rest[..word] == b"r" {
+        let hashes: usize = rest[1..].iter().take_while(|&&b| b == b'#').count();
+        let quoted: &[u8] = &rest[1 + hashes..];
+        let quote: &str = match quoted.starts_with(b"\"\"\"") {
+            true => "\"\"\"",
+            false if quoted.starts_with(b"\"") => "\"",
+            false => return word,
+        };
+        *state = ScanState::RawString(format!("{}{}", quote, "#".repeat(hashes)));
+        return 1 + hashes + quote.len();
+    }
+    return word;
+}
+
+#[cfg(feature = "parallel")]
+fn lex_chunk(code: &str, range: Range<usize>, line: usize) -> Result<Vec<Token>, LexError> {
+    let mut tokenizer: Tokenizer = Tokenizer::init(&code[range.clone()]);
+    tokenizer.line = line;
+    tokenizer.offset = range.start;
+    let tokens: Vec<Token> = tokenizer
+        .collect::<Result<Vec<Token>, LexError>>()
+        .map_err(|error| error.offset_by(range.start))?;
+    let shift = |span: Range<usize>| span.start + range.start..span.end + range.start;
+    return Ok(tokens
+        .into_iter()
+        .map(|token| Token {
+            span: shift(token.span.clone()),
+            ..token
+        })
+        .collect());
+}
+
+// Lex the edited code again starting from the last Token before the edit, since the edit can
+// make it continue further, e.g. 'r' followed by a comment becomes a raw string. Tokens right
+// before it are lexed again too, as finding their end looked at it, e.g. '5' in '5.;'
+// The old Tokens are reused from the first one after the edit where lexing gets back in step,
+// so the result is the same as lexing all of the new code. Trivia must not be in 'old'
+#[allow(dead_code)]
+pub(crate) fn relex(old: &[Token], code: &str, edit: Edit) -> Result<Vec<Token>, LexError> {
+    let before: usize = old.partition_point(|token| token.span.start < edit.range.start);
+    let mut kept: usize = before.saturating_sub(1);
+    while kept > 0 && old[kept - 1].span.end == old[kept].span.start {
+        kept -= 1;
+    }
+    let mut tokens: Vec<Token> = old[..kept].to_vec();
+    let mut tokenizer: Tokenizer = Tokenizer::init(code);
+    if before > 0 {
+        let restart: &Token = &old[kept];
+        tokenizer.cursor = restart.span.start;
+        tokenizer.line = restart.line;
+        tokenizer.column = restart.column;
+    }
+
+    // Old Tokens after the edit are only moved by how much the edit changed the length
+    let edit_end: usize = edit.range.start + edit.text.len();
+    let shift = |offset: usize| offset - edit.range.end + edit_end;
+    let mut rest = old[kept..]
+        .iter()
+        .skip_while(|token| token.span.start < edit.range.end)
+        .peekable();
+    while let Some(token) = tokenizer.get_next_token()? {
+        while rest
+            .next_if(|old| shift(old.span.start) < token.span.start)
+            .is_some()
+        {}
+        let in_step: bool = rest
+            .peek()
+            .is_some_and(|old| shift(old.span.start) == token.span.start);
+        if token.span.start >= edit_end && in_step {
+            let first: &Token = rest.peek().unwrap();
+            let (line, column): (usize, usize) = (first.line, first.column);
+            tokens.extend(rest.map(|old| Token {
+                span: shift(old.span.start)..shift(old.span.end),
+                line: old.line - line + token.line,
+                // Only the Tokens on the same line as the edit move sideways
+                column: match old.line == line {
+                    true => old.column - column + token.column,
+                    false => old.column,
+                },
+                ..old.clone()
+            }));
+            return Ok(tokens);
+        }
+        tokens.push(token);
+    }
+    return Ok(tokens);
+}
+
+// Token lookahead for the parser, lexing only as far as it has been peeked
+pub(crate) struct TokenStream<'a> {
+    tokens: Box<dyn Iterator<Item = Result<Token, LexError>> + 'a>,
+    buffer: VecDeque<Token>,
+    // End of the last lexed Token, used as the position of the end of input
+    end: usize,
+    // End of the last taken Token, where the node parsed so far ends
+    previous_end: usize,
+}
+
+impl<'a> TokenStream<'a> {
+    pub(crate) fn init(tokens: impl Iterator<Item = Result<Token, LexError>> + 'a) -> Self {
+        Self {
+            tokens: Box::new(tokens),
+            buffer: VecDeque::new(),
+            end: 0,
+            previous_end: 0,
+        }
+    }
+
+    pub(crate) fn peek(&mut self) -> Result<Option<&Token>, LexError> {
+        self.peek_nth(0)
+    }
+
+    // End of the last lexed Token, where a missing Token would have been
+    pub(crate) fn end(&self) -> usize {
+        self.end
+    }
+
+    pub(crate) fn previous_end(&self) -> usize {
+        self.previous_end
+    }
+
+    // Peek the Token n positions after the next one
+    pub(crate) fn peek_nth(&mut self, n: usize) -> Result<Option<&Token>, LexError> {
+        while self.buffer.len() <= n {
+            match self.tokens.next() {
+                Some(token) => {
+                    let token: Token = token?;
+                    self.end = token.span.end;
+                    self.buffer.push_back(token);
+                }
+                None => return Ok(None),
+            }
+        }
+        return Ok(self.buffer.get(n));
+    }
+
+    pub(crate) fn next(&mut self) -> Result<Option<Token>, LexError> {
+        self.peek()?;
+        let token: Option<Token> = self.buffer.pop_front();
+        if let Some(token) = &token {
+            self.previous_end = token.span.end;
+        }
+        return Ok(token);
+    }
+
+    // Take the next Token if it is of the expected TokenType
+    pub(crate) fn expect(&mut self, token_type: TokenType) -> Result<Token, ParseError> {
+        match self.peek()? {
+            Some(token) if token.token_type == token_type => Ok(self.next()?.unwrap()),
+            Some(token) => Err(ParseError {
+                message: format!("Expected {:?} but found '{}'", token_type, token.lexeme),
+                span: token.span.clone(),
+            }),
+            None => Err(ParseError {
+                message: format!("Expected {:?} but found nothing", token_type),
+                span: self.end..self.end,
+            }),
+        }
+    }
+
+    // Take the next Token if it has the expected lexeme, e.g. a closing bracket
+    pub(crate) fn expect_lexeme(&mut self, lexeme: &str) -> Result<Token, ParseError> {
+        match self.peek()? {
+            Some(token) if token.lexeme == lexeme => Ok(self.next()?.unwrap()),
+            Some(token) => Err(ParseError {
+                message: format!("Expected '{}' but found '{}'", lexeme, token.lexeme),
+                span: token.span.clone(),
+            }),
+            None => Err(ParseError {
+                message: format!("Expected '{}' but found nothing", lexeme),
+                span: self.end..self.end,
+            }),
+        }
+    }
+
+    // Take the next Token if it is of the given TokenType
+    pub(crate) fn next_if_type(
+        &mut self,
+        token_type: TokenType,
+    ) -> Result<Option<Token>, LexError> {
+        match self.peek()? {
+            Some(token) if token.token_type == token_type => self.next(),
+            _ => Ok(None),
+        }
+    }
+
+    // Take the next Token if its lexeme is one of the given ones
+    pub(crate) fn next_if_lexeme(&mut self, lexemes: &[&str]) -> Result<Option<Token>, LexError> {
+        match self.peek()? {
+            Some(token) if lexemes.contains(&token.lexeme.as_str()) => self.next(),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defs::TOKEN_REGEXES;
+    use crate::utils::{strip_digit_separators, token_at, token_stats};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_lexing_comments() {
+        let mut parser: Parser = Parser::init("/* multi\nline */ 42 // single-line");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_token_serde_round_trip() {
+        let mut parser: Parser = Parser::init("int x = 0x2A; // answer\nprint(\"hi\");");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let json: String = serde_json::to_string(&tokens).unwrap();
+        let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, tokens);
+    }
+
+    #[test]
+    fn test_token_type_display() {
+        let token_types: Vec<(TokenType, &str)> = vec![
+            (TokenType::AssignmentOperator, "assignment operator"),
+            (TokenType::BinaryOperator, "binary operator"),
+            (TokenType::Bool, "boolean literal"),
+            (TokenType::DataType, "data type"),
+            (TokenType::Delimiter, "delimiter"),
+            (TokenType::Identifier, "identifier"),
+            (TokenType::Literal(DataType::Character), "character literal"),
+            (TokenType::Literal(DataType::Float), "float literal"),
+            (TokenType::Literal(DataType::Integer), "integer literal"),
+            (TokenType::Literal(DataType::String), "string literal"),
+            (TokenType::Literal(DataType::Boolean), "literal"),
+            (TokenType::Literal(DataType::None), "literal"),
+            (TokenType::Literal(DataType::Void), "literal"),
+            (TokenType::Keyword, "keyword"),
+            (TokenType::Null, "none"),
+            (TokenType::UnaryOperator, "unary operator"),
+            (TokenType::None, "whitespace or comment"),
+        ];
+        for (token_type, name) in token_types {
+            // New TokenTypes fail to compile here until they are added to the list above
+            match token_type {
+                TokenType::AssignmentOperator
+                | TokenType::BinaryOperator
+                | TokenType::Bool
+                | TokenType::DataType
+                | TokenType::Delimiter
+                | TokenType::Identifier
+                | TokenType::Literal(_)
+                | TokenType::Keyword
+                | TokenType::Null
+                | TokenType::UnaryOperator
+                | TokenType::None => assert_eq!(token_type.to_string(), name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lexing_trivia() {
+        let mut parser: Parser = Parser::init("a b").with_trivia(true);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", " ", "b"]);
+        assert_eq!(tokens[1].token_type, TokenType::None);
+        assert_eq!(tokens[1].span, 1..2);
+
+        let mut parser: Parser = Parser::init("/* c */ a // d\n").with_trivia(true);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["/* c */", " ", "a", " ", "// d", "\n"]
+        );
+
+        let mut parser: Parser = Parser::init("a b").with_trivia(false);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_lexing_hash_comments() {
+        let mut parser: Parser = Parser::init("# note\n42");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+        assert_eq!(tokens[0].line, 2);
+
+        // Raw strings keep their hashes
+        let mut parser: Parser = Parser::init("s = r#\"#\"#; # comment");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["s", "=", "r#\"#\"#", ";"]);
+    }
+
+    #[test]
+    fn test_lexing_shebang() {
+        let mut parser: Parser = Parser::init("#!/usr/bin/env pog\n42");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+        assert_eq!((tokens[0].line, tokens[0].column), (2, 1));
+
+        // Only the very first bytes of the file can be a shebang
+        let mut tokenizer: Tokenizer = Tokenizer::init("42\n#!/usr/bin/env pog");
+        tokenizer.advance_cursor(3);
+        assert_eq!(tokenizer.scan_shebang(), None);
+        let mut tokenizer: Tokenizer = Tokenizer::init(" #!/usr/bin/env pog");
+        tokenizer.advance_cursor(1);
+        assert_eq!(tokenizer.scan_shebang(), None);
+    }
+
+    #[test]
+    fn test_lexing_nested_comments() {
+        let mut parser: Parser = Parser::init("/* a /* b */ c */ 42 /* 1 /* 2 /* 3 */ */ */");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+
+        // The outer comment is still open after the inner one closes
+        let mut parser: Parser = Parser::init("/* a /* b */ c");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedComment { start: 0 })
+        );
+    }
+
+    #[test]
+    fn test_lexing_unterminated_comment() {
+        let mut parser: Parser = Parser::init("/* open");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedComment { start: 0 })
+        );
+
+        let mut parser: Parser = Parser::init("a /* ** / */ b");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "b"]);
+
+        let mut parser: Parser = Parser::init("a /**/ b /*/ c");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedComment { start: 9 })
+        );
+    }
+
+    #[test]
+    fn test_lexing_boolean() {
+        let mut parser: Parser = Parser::init("true false");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["true", "false"]);
+        assert_token_types(&tokens, TokenType::Bool);
+
+        let mut parser: Parser = Parser::init("trueish truest falsey");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["trueish", "truest", "falsey"]);
+        assert_token_types(&tokens, TokenType::Identifier);
+    }
+
+    #[test]
+    fn test_lexing_none() {
+        let mut parser: Parser = Parser::init("none");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["none"]);
+        assert_token_types(&tokens, TokenType::Null);
+
+        let mut parser: Parser = Parser::init("nonexistent nones");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["nonexistent", "nones"]);
+        assert_token_types(&tokens, TokenType::Identifier);
+    }
+
+    #[test]
+    fn test_lexing_character() {
+        let mut parser: Parser = Parser::init("'c'");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["'c'"]);
+    }
+
+    #[test]
+    fn test_lexing_character_escapes() {
+        let mut parser: Parser = Parser::init(r"'\n' '\'' '\\' '\0'");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec![r"'\n'", r"'\''", r"'\\'", r"'\0'"]
+        );
+        assert_token_types(&tokens, TokenType::Literal(DataType::Character));
+        let values: Vec<Option<String>> = tokens.into_iter().map(|token| token.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                Some("\n".to_string()),
+                Some("'".to_string()),
+                Some("\\".to_string()),
+                Some("\0".to_string())
+            ]
+        );
+
+        // Exactly one character is required
+        for literal in ["'ab'", "''"] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::InvalidCharacter {
+                    span: 0..literal.len(),
+                    literal: literal.to_string(),
+                })
+            );
+        }
+
+        // Character literals do not continue to the next line
+        let mut parser: Parser = Parser::init("'a\n'");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_lexing_integer() {
+        let mut parser: Parser = Parser::init("42");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+    }
+
+    #[test]
+    fn test_lexing_hexadecimal_integer() {
+        let mut parser: Parser = Parser::init("0xFF 0x1a2b 0xaBc");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["0xFF", "0x1a2b", "0xaBc"]);
+        assert_token_types(&tokens, TokenType::Literal(DataType::Integer));
+        let values: Vec<Option<String>> = tokens.into_iter().map(|token| token.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                Some("255".to_string()),
+                Some("6699".to_string()),
+                Some("2748".to_string())
+            ]
+        );
+
+        // Hexadecimal prefix requires digits
+        let mut parser: Parser = Parser::init("0x");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::InvalidNumber {
+                span: 0..2,
+                literal: "0x".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_lexing_binary_and_octal_integer() {
+        let mut parser: Parser = Parser::init("0b1010 0o755");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["0b1010", "0o755"]);
+        assert_token_types(&tokens, TokenType::Literal(DataType::Integer));
+        assert_eq!(tokens[0].value, Some("10".to_string()));
+        assert_eq!(tokens[1].value, Some("493".to_string()));
+
+        // Digits outside of the radix are not truncated
+        for literal in ["0b2", "0o9", "0b102"] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::InvalidNumber {
+                    span: 0..literal.len(),
+                    literal: literal.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexing_digit_separators() {
+        let mut parser: Parser = Parser::init("1_000 0xFF_FF 0b1_0 1_0.0_1 1_0e1_0");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["1_000", "0xFF_FF", "0b1_0", "1_0.0_1", "1_0e1_0"]
+        );
+        assert_eq!(tokens[0].value, Some("1000".to_string()));
+        assert_eq!(tokens[1].value, Some("65535".to_string()));
+        assert_eq!(strip_digit_separators(tokens[3].lexeme.as_str()), "10.01");
+
+        // Leading underscore makes an identifier
+        let mut parser: Parser = Parser::init("_1");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_token_types(&tokens, TokenType::Identifier);
+
+        // Trailing and doubled underscores are not allowed
+        for literal in ["1_", "1__0", "0x_1"] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::InvalidNumber {
+                    span: 0..literal.len(),
+                    literal: literal.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexing_float() {
+        let mut parser: Parser = Parser::init("3.14 0.0");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["3.14", "0.0"]);
+        assert_token_types(&tokens, TokenType::Literal(DataType::Float));
+
+        // Without digits after it the point is member access on an integer
+        let mut parser: Parser = Parser::init("5.");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["5", "."]);
+        assert_eq!(tokens[0].token_type, TokenType::Literal(DataType::Integer));
+        assert_eq!(tokens[1].token_type, TokenType::Delimiter);
+    }
+
+    #[test]
+    fn test_lexing_scientific_float() {
+        let mut parser: Parser = Parser::init("1e10 2.5E-3 6.022e23 7e+2");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["1e10", "2.5E-3", "6.022e23", "7e+2"]
+        );
+        assert_token_types(&tokens, TokenType::Literal(DataType::Float));
+
+        // Exponent requires at least one digit
+        let mut parser: Parser = Parser::init("1e");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::InvalidNumber {
+                span: 0..2,
+                literal: "1e".to_string(),
+            })
+        );
+        let mut parser: Parser = Parser::init("1e+;");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::InvalidNumber {
+                span: 0..3,
+                literal: "1e+".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_token_stats() {
+        let mut parser: Parser = Parser::init("let x: int = 1 + 2; print(x, \"s\");");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let stats: HashMap<TokenType, usize> = token_stats(&tokens);
+        let expected: Vec<(TokenType, usize)> = vec![
+            (TokenType::Keyword, 1),
+            (TokenType::Identifier, 3),
+            (TokenType::Delimiter, 6),
+            (TokenType::DataType, 1),
+            (TokenType::Literal(DataType::Integer), 2),
+            (TokenType::BinaryOperator, 2),
+            (TokenType::Literal(DataType::String), 1),
+        ];
+        assert_eq!(stats, expected.into_iter().collect());
+        assert!(token_stats(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_token_at() {
+        let mut parser: Parser = Parser::init("let  answer = 42; // done");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let offsets: Vec<(usize, Option<&str>)> = vec![
+            (0, Some("let")),
+            (2, Some("let")),
+            (5, Some("answer")),
+            (10, Some("answer")),
+            (12, Some("=")),
+            (15, Some("42")),
+            (16, Some(";")),
+            // Whitespace, comments and offsets past the end are not Tokens
+            (3, None),
+            (4, None),
+            (11, None),
+            (20, None),
+            (100, None),
+        ];
+        for (offset, lexeme) in offsets {
+            let token: Option<&Token> = token_at(&tokens, offset);
+            assert_eq!(
+                token.map(|token| token.lexeme.as_str()),
+                lexeme,
+                "{}",
+                offset
+            );
         }
+        assert_eq!(token_at(&[], 0), None);
     }
 
-    pub(crate) fn parse(&mut self) -> Vec<&str> {
-        let mut tokens: Vec<&str> = vec![];
-        loop {
-            let token: Option<&str> = self.tokenizer.get_next_token();
-            if token.is_none() {
-                break;
+    #[test]
+    fn test_lexing_integer_overflow() {
+        // The largest integer literal is i64::MAX in any radix
+        let mut parser: Parser = Parser::init("9223372036854775807 0x7FFF_FFFF_FFFF_FFFF");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_token_types(&tokens, TokenType::Literal(DataType::Integer));
+        assert_eq!(tokens[0].value, Some(i64::MAX.to_string()));
+        assert_eq!(tokens[1].value, Some(i64::MAX.to_string()));
+
+        let mut parser: Parser = Parser::init("let x = 99999999999999999999;");
+        let error: LexError = parser.parse().unwrap_err();
+        assert_eq!(
+            error,
+            LexError::IntegerOverflow {
+                span: 8..28,
+                literal: "99999999999999999999".to_string(),
             }
-            tokens.push(token.unwrap());
+        );
+        assert_eq!(
+            error.to_string(),
+            "Integer literal at byte 8 does not fit in 64 bits: '99999999999999999999'"
+        );
+
+        // Literals too large even for the lexer and suffixed literals overflow the same way
+        for literal in [
+            "9223372036854775808",
+            "0x8000000000000000",
+            "340282366920938463463374607431768211456",
+            "9223372036854775808i",
+        ] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::IntegerOverflow {
+                    span: 0..literal.len(),
+                    literal: literal.to_string(),
+                }),
+                "{}",
+                literal
+            );
         }
-        return tokens;
     }
-}
 
-#[derive(Debug)]
-struct Tokenizer<'a> {
-    code: &'a str,
-    cursor: usize,
-}
+    #[test]
+    fn test_lexing_number_suffixes() {
+        let mut parser: Parser = Parser::init("42i 10 0xFFi");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42i", "10", "0xFFi"]);
+        assert_token_types(&tokens, TokenType::Literal(DataType::Integer));
+        assert_eq!(tokens[0].value, Some("42".to_string()));
+        assert_eq!(tokens[2].value, Some("255".to_string()));
 
-impl<'a> Tokenizer<'a> {
-    fn init(code: &'a str) -> Self {
-        Self {
-            code: code,
-            cursor: 0,
+        // Integers with a float suffix become floats
+        let mut parser: Parser = Parser::init("3.14f 2f 1e3f");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["3.14f", "2f", "1e3f"]);
+        assert_token_types(&tokens, TokenType::Literal(DataType::Float));
+        assert_eq!(tokens[0].value, Some("3.14".to_string()));
+        assert_eq!(tokens[1].value, Some("2.0".to_string()));
+        assert_eq!(tokens[0].span, 0..5);
+
+        // Unknown suffixes and suffixes not matching the number are errors
+        for literal in ["42z", "3.14i", "1.5u", "0b1f", "42if", "10u", "0xFFu"] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::InvalidNumber {
+                    span: 0..literal.len(),
+                    literal: literal.to_string(),
+                }),
+                "{}",
+                literal
+            );
         }
+
+        // Unsigned integers are not signed ones with the suffix dropped
+        assert_ne!(Parser::init("10u").parse(), Parser::init("10i").parse());
     }
 
-    fn has_more_tokens(&self) -> bool {
-        self.cursor < self.code.len()
+    #[test]
+    fn test_lexing_string() {
+        let mut parser: Parser = Parser::init("\"This is String\"");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["\"This is String\""]);
     }
 
-    fn get_next_token(&mut self) -> Option<&'a str> {
-        if !self.has_more_tokens() {
-            return None;
-        }
+    #[test]
+    fn test_lexing_raw_string() {
+        let mut parser: Parser = Parser::init(r##"r"C:\path\n" r#"he said "hi""#"##);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec![r#"r"C:\path\n""#, r##"r#"he said "hi""#"##]
+        );
+        assert_token_types(&tokens, TokenType::Literal(DataType::String));
+        assert_eq!(tokens[0].value, Some(r"C:\path\n".to_string()));
+        assert_eq!(tokens[1].value, Some(r#"he said "hi""#.to_string()));
 
-        // Test if the remaining code matches with any Token regex
-        let unparsed_code: &str = self.code.split_at(self.cursor).1;
-        for (regex, token_type) in TOKEN_REGEXES.entries() {
-            let captures: Option<Captures> = Regex::new(regex).unwrap().captures(unparsed_code);
-            if !captures.is_none() {
-                // Take match from capture group if it is explicitly specified
-                let whole_match: Option<Match> = captures.as_ref().unwrap().get(0);
-                let mut token_match: Option<Match> = captures.unwrap().get(1);
-                if token_match.is_none() {
-                    token_match = whole_match;
-                }
+        // Without a quote after the prefix 'r' is still an identifier
+        let mut parser: Parser = Parser::init("r + rx");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["r", "+", "rx"]);
 
-                // Move cursor to the end of the parsed Token
-                self.cursor += whole_match.unwrap().end();
+        // The closing quote needs the same amount of hashes as the opening one
+        let mut parser: Parser = Parser::init(r##"x = r#"no end";"##);
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedString { start: 4 })
+        );
+    }
 
-                // Token should be skipped, e.g. whitespace or comment
-                if token_type == &TokenType::None {
-                    return self.get_next_token();
-                }
-                return Some(token_match.unwrap().as_str());
+    #[test]
+    fn test_lexing_triple_quoted_string() {
+        let mut parser: Parser = Parser::init("\"\"\"first \"line\"\n\tsecond\\n\"\"\"");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_type, TokenType::Literal(DataType::String));
+        assert_eq!(
+            tokens[0].value,
+            Some("first \"line\"\n\tsecond\n".to_string())
+        );
+        assert_eq!(parser.tokens().line, 2);
+
+        // Combined with the raw prefix escapes are kept as is
+        let mut parser: Parser = Parser::init(r#"r"""a\n"b""""#);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(tokens[0].value, Some(r#"a\n"b"#.to_string()));
+
+        // An empty string is not the start of a triple-quoted one
+        let mut parser: Parser = Parser::init("\"\" \"\"");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["\"\"", "\"\""]);
+
+        let mut parser: Parser = Parser::init("x = \"\"\"open\n\"\"");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedString { start: 4 })
+        );
+    }
+
+    #[test]
+    fn test_lexing_unterminated_literals() {
+        let mut parser: Parser = Parser::init("\"abc");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedString { start: 0 })
+        );
+
+        let mut parser: Parser = Parser::init("s = \"oops;\nx = 1;");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedString { start: 4 })
+        );
+
+        let mut parser: Parser = Parser::init("'a");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedCharacter { start: 0 })
+        );
+
+        // Character literals cannot continue on the next line
+        let mut parser: Parser = Parser::init("c = 'a\n'");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnterminatedCharacter { start: 4 })
+        );
+    }
+
+    #[test]
+    fn test_lexing_unknown_token() {
+        // Token regexes must not skip over the unknown '@' to match '42'
+        let mut parser: Parser = Parser::init("@42");
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::UnknownToken {
+                offset: 0,
+                snippet: "@42".to_string(),
+                suggestion: None,
+            })
+        );
+
+        let mut parser: Parser = Parser::init("a = §;");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_lexing_suggestion() {
+        let mut parser: Parser = Parser::init("a =< b");
+        let error: LexError = parser.parse().unwrap_err();
+        assert_eq!(
+            error,
+            LexError::UnknownToken {
+                offset: 2,
+                snippet: "=< b".to_string(),
+                suggestion: Some("<=".to_string()),
             }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unknown Token at byte 2: '=< b', did you mean '<='?"
+        );
+
+        // Nothing is close to an unknown symbol on its own
+        let mut parser: Parser = Parser::init("a ~ b");
+        assert!(matches!(
+            parser.parse(),
+            Err(LexError::UnknownToken {
+                suggestion: None,
+                ..
+            })
+        ));
+
+        let mut parser: Parser = Parser::init("a @= b");
+        assert!(matches!(
+            parser.parse(),
+            Err(LexError::UnknownToken {
+                suggestion: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_lexing_with_recovery() {
+        let mut parser: Parser = Parser::init("a @ b § c");
+        let (tokens, errors): (Vec<Token>, Vec<LexError>) = parser.parse_recover();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "b", "c"]);
+        assert_eq!(
+            errors,
+            vec![
+                LexError::UnknownToken {
+                    offset: 2,
+                    snippet: "@ b § c".to_string(),
+                    suggestion: None,
+                },
+                LexError::UnknownToken {
+                    offset: 6,
+                    snippet: "§ c".to_string(),
+                    suggestion: None,
+                },
+            ]
+        );
+
+        // Invalid literals are skipped as a whole
+        let mut parser: Parser = Parser::init("12abc \"\\q\" 'ab' x");
+        let (tokens, errors): (Vec<Token>, Vec<LexError>) = parser.parse_recover();
+        assert_eq!(get_lexemes(&tokens), vec!["x"]);
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_lexing_multibyte_characters() {
+        let mut parser: Parser = Parser::init("\"héllo\" // 🦀 comment\n/* 🎉 */ 42");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["\"héllo\"", "42"]);
+    }
+
+    #[test]
+    fn test_lexing_lazily() {
+        // The unknown Token is never reached when only the first Tokens are taken
+        let mut parser: Parser = Parser::init("a b @");
+        let tokens: Vec<Token> = parser
+            .tokens()
+            .by_ref()
+            .take(2)
+            .collect::<Result<Vec<Token>, LexError>>()
+            .unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "b"]);
+
+        // Iteration ends after the first error
+        assert!(parser.tokens().next().unwrap().is_err());
+        assert!(parser.tokens().next().is_none());
+    }
+
+    #[test]
+    fn test_lexing_stream() {
+        // Tokens continuing on the next lines are only taken once all of them are read
+        let code: &str =
+            "#!/usr/bin/env pog\nlet x = 1;\r\n/* a\n/* b */\n*/ s = \"\"\"\nä\"\"\";\n\nx";
+        let mut parser: Parser = Parser::init(code);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let stream: StreamTokenizer<Cursor<Vec<u8>>> =
+            StreamTokenizer::init(Cursor::new(code.as_bytes().to_vec()));
+        assert_eq!(stream.collect::<Result<Vec<Token>, LexError>>(), Ok(tokens));
+
+        // Errors are at the same position as when lexing all of the code at once
+        for code in ["x;\n1 @ 2", "x;\n/* a\nb", "x;\n\"\"\"", "\n\n12abc"] {
+            let mut parser: Parser = Parser::init(code);
+            let stream: StreamTokenizer<Cursor<Vec<u8>>> =
+                StreamTokenizer::init(Cursor::new(code.as_bytes().to_vec()));
+            assert_eq!(
+                stream.collect::<Vec<Result<Token, LexError>>>(),
+                parser.tokens().collect::<Vec<Result<Token, LexError>>>(),
+                "{}",
+                code
+            );
         }
 
-        // TODO: Enhance error reporting
-        panic!(
-            "Unknown Token at the start of the following code:\n{}",
-            unparsed_code
-        )
+        let stream: StreamTokenizer<Cursor<Vec<u8>>> =
+            StreamTokenizer::init(Cursor::new(b"x;\n\xFF\n".to_vec()));
+        let errors: Vec<LexError> = stream.filter_map(Result::err).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span(), 3..3);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_lexing_in_parallel() {
+        // Strings and comments spanning lines end up on both sides of chunk boundaries
+        let lines: &str = "let s = \"a\nb\"; /* c\n/* d */\n*/ x += 1_0;\nr\"\"\"\ne\"\"\" # f\n";
+        let code: String = format!("#!/usr/bin/env pog\n{}", lines.repeat(2_000));
+        let mut parser: Parser = Parser::init(&code);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(lex_parallel(&code), Ok(tokens.clone()));
+        assert_eq!(lex_chunks(&code, 1_000), Ok(tokens));
+
+        // Chunks of a single line split every Token continuing on the next line
+        for code in [lines, "x;\n/* a\nb", "\"\n\n1 @ 2\n", "x\n\n12abc\ny"] {
+            let mut parser: Parser = Parser::init(code);
+            assert_eq!(lex_chunks(code, 1), parser.parse(), "{}", code);
+        }
+
+        // A comment spanning many chunks keeps them from splitting inside it
+        let code: String = format!(
+            "let c = '\"';\n/* {} */ x;\nr#\"\n\"#; \"\"\"\n\"\"\" y;\n",
+            "\"a\" // b\n".repeat(20_000)
+        );
+        let mut parser: Parser = Parser::init(&code);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        let comment_end: usize = code.find("*/ x;\n").unwrap() + 6;
+        assert_eq!(split_lines(&code, 1_000)[0], (0..comment_end, 1));
+        assert_eq!(lex_chunks(&code, 1_000), Ok(tokens));
+    }
 
     #[test]
-    fn test_lexing_comments() {
-        let mut parser: Parser = Parser::init("/* multi\nline */ 42 // single-line");
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["42"]);
+    fn test_relexing() {
+        let code: &str = "let x = 5.;\nfun f(a: int) {\n    return a + 1;\n}\nprint(f(x));\n";
+        let mut parser: Parser = Parser::init(code);
+        let old: Vec<Token> = parser.parse().unwrap();
+
+        // Each edit replaces a byte range of the code with the text
+        let edits: Vec<(Range<usize>, &str)> = vec![
+            (39..40, "b"),
+            (39..40, "answer"),
+            (10..10, "3"),
+            (0..3, "const"),
+            (12..12, "\n\n"),
+            (12..28, ""),
+            (43..43, "\"x\" + "),
+            (45..46, ""),
+            (48..48, "1; "),
+            (61..61, "x"),
+            (16..16, "/* "),
+        ];
+        for (range, text) in edits {
+            let mut edited: String = code.to_string();
+            edited.replace_range(range.clone(), text);
+            let mut parser: Parser = Parser::init(&edited);
+            let edit: Edit = Edit {
+                range: range,
+                text: text.to_string(),
+            };
+            assert_eq!(
+                relex(&old, &edited, edit.clone()),
+                parser.parse(),
+                "{:?}",
+                edit
+            );
+        }
+
+        // Inserting a quote turns the Token before the edit and the comment after it into a
+        // raw string, or into an unterminated one
+        let old: Vec<Token> = Parser::init("r####x y\"####").parse().unwrap();
+        let edits: Vec<(Range<usize>, &str)> = vec![(5..5, "\""), (5..9, "\"x y")];
+        for (range, text) in edits {
+            let mut edited: String = "r####x y\"####".to_string();
+            edited.replace_range(range.clone(), text);
+            let edit: Edit = Edit {
+                range: range,
+                text: text.to_string(),
+            };
+            assert_eq!(
+                relex(&old, &edited, edit.clone()),
+                Parser::init(&edited).parse(),
+                "{:?}",
+                edit
+            );
+        }
+        let edit: Edit = Edit {
+            range: 5..5,
+            text: "\"".to_string(),
+        };
+        assert_eq!(
+            relex(&old, "r####\"x y\"####", edit).unwrap()[0].lexeme,
+            "r####\"x y\"####"
+        );
     }
 
     #[test]
-    fn test_lexing_character() {
-        let mut parser: Parser = Parser::init("'c'");
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["'c'"]);
+    fn test_token_stream_peeking() {
+        let mut parser: Parser = Parser::init("a = 42");
+        let mut stream: TokenStream = TokenStream::init(parser.tokens());
+
+        // Peeking does not consume Tokens
+        assert_eq!(stream.peek().unwrap().unwrap().lexeme, "a");
+        assert_eq!(stream.peek().unwrap().unwrap().lexeme, "a");
+        assert_eq!(stream.peek_nth(2).unwrap().unwrap().lexeme, "42");
+        assert!(stream.peek_nth(3).unwrap().is_none());
+
+        // Next advances past the peeked Token
+        assert_eq!(stream.next().unwrap().unwrap().lexeme, "a");
+        assert_eq!(stream.peek().unwrap().unwrap().lexeme, "=");
     }
 
     #[test]
-    fn test_lexing_integer() {
-        let mut parser: Parser = Parser::init("42");
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["42"]);
+    fn test_token_stream_expect() {
+        let mut parser: Parser = Parser::init("a = ");
+        let mut stream: TokenStream = TokenStream::init(parser.tokens());
+        assert_eq!(stream.expect(TokenType::Identifier).unwrap().lexeme, "a");
+        assert_eq!(
+            stream.expect(TokenType::Identifier),
+            Err(ParseError {
+                message: "Expected Identifier but found '='".to_string(),
+                span: 2..3,
+            })
+        );
+        assert_eq!(
+            stream.expect(TokenType::BinaryOperator).unwrap().lexeme,
+            "="
+        );
+        assert_eq!(stream.expect(TokenType::Identifier).unwrap_err().span, 3..3);
     }
 
     #[test]
-    fn test_lexing_string() {
-        let mut parser: Parser = Parser::init("\"This is String\"");
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["\"This is String\""]);
+    fn test_lexing_long_skipped_input() {
+        let code: String = " ".repeat(100_000) + "42";
+        let mut parser: Parser = Parser::init(code.as_str());
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+
+        // Every comment and whitespace run is skipped separately
+        let code: String = "/**/ ".repeat(100_000) + "42";
+        let mut parser: Parser = Parser::init(code.as_str());
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["42"]);
+    }
+
+    #[test]
+    fn test_lexing_large_input() {
+        // 10k lines would take minutes if the regexes were compiled per Token
+        let code: String = "int a = b + 42; // comment\n".repeat(10_000);
+        let mut parser: Parser = Parser::init(code.as_str());
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(tokens.len(), 70_000);
+    }
+
+    fn get_lexemes(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|token| token.lexeme.as_str()).collect()
+    }
+
+    fn assert_token_types(tokens: &[Token], token_type: TokenType) {
+        for token in tokens {
+            assert_eq!(token.token_type, token_type, "'{}'", token.lexeme);
+        }
     }
 
     fn count_token_types(token_type: TokenType) -> usize {
@@ -122,29 +1810,153 @@ mod tests {
         return keyword_count;
     }
 
+    #[test]
+    fn test_lexing_string_escapes() {
+        let mut parser: Parser = Parser::init(r#""say \"hi\"" "line\n" "\t\\\0""#);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec![r#""say \"hi\"""#, r#""line\n""#, r#""\t\\\0""#]
+        );
+        assert_token_types(&tokens, TokenType::Literal(DataType::String));
+        assert_eq!(tokens[0].value, Some("say \"hi\"".to_string()));
+        assert_eq!(tokens[1].value, Some("line\n".to_string()));
+        assert_eq!(tokens[2].value, Some("\t\\\0".to_string()));
+
+        let mut parser: Parser = Parser::init(r#"a = "bad \q";"#);
+        assert_eq!(
+            parser.parse(),
+            Err(LexError::InvalidEscape {
+                span: 9..11,
+                escape: r"\q".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_lexing_unicode_escapes() {
+        let mut parser: Parser = Parser::init(r#""\u{1F600}" '\u{41}' "\u{e9}t\u{E9}""#);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec![r#""\u{1F600}""#, r"'\u{41}'", r#""\u{e9}t\u{E9}""#]
+        );
+        assert_eq!(tokens[0].value, Some("😀".to_string()));
+        assert_eq!(tokens[1].value, Some("A".to_string()));
+        assert_eq!(tokens[2].value, Some("été".to_string()));
+
+        // Out of range, surrogate and malformed escapes
+        for (literal, escape) in [
+            (r#""a\u{110000}""#, r"\u{110000}"),
+            (r#""a\u{D800}""#, r"\u{D800}"),
+            (r#""a\u{}""#, r"\u{}"),
+            (r#""a\u{1234567}""#, r"\u{1234567}"),
+            (r#""a\u{12G}""#, r"\u{12G}"),
+            (r#""a\u41""#, r"\u"),
+            (r#""a\u{41""#, r"\u{41"),
+        ] {
+            let mut parser: Parser = Parser::init(literal);
+            assert_eq!(
+                parser.parse(),
+                Err(LexError::InvalidEscape {
+                    span: 2..2 + escape.len(),
+                    escape: escape.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexing_unicode_identifiers() {
+        let mut parser: Parser = Parser::init("café naïve _x $y a1");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["café", "naïve", "_x", "$y", "a1"]
+        );
+        assert_token_types(&tokens, TokenType::Identifier);
+
+        // Combining mark cannot start an identifier
+        let mut parser: Parser = Parser::init("\u{301}a");
+        assert!(parser.parse().is_err());
+
+        // Digit starts a number instead
+        let mut parser: Parser = Parser::init("1é");
+        assert!(parser.parse().is_err());
+    }
+
     #[test]
     fn test_lexing_datatypes() {
         let datatype_count: usize = count_token_types(TokenType::DataType);
-        assert_eq!(datatype_count, 3, "Exhaustive testing of DataTypes");
+        assert_eq!(datatype_count, 6, "Exhaustive testing of DataTypes");
 
-        let datatypes: &str = "char int str";
+        let datatypes: &str = "bool char float int str void";
         let mut parser: Parser = Parser::init(datatypes);
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["char", "int", "str",]);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["bool", "char", "float", "int", "str", "void"]
+        );
+        assert_token_types(&tokens, TokenType::DataType);
     }
 
     #[test]
     fn test_lexing_keywords() {
         let keyword_count: usize = count_token_types(TokenType::Keyword);
-        assert_eq!(keyword_count, 8, "Exhaustive testing of Keywords");
+        assert_eq!(keyword_count, 16, "Exhaustive testing of Keywords");
 
-        let keywords: &str = "break continue elif else fun if return while";
-        let mut parser: Parser = Parser::init(keywords);
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(
-            tokens,
-            vec!["break", "continue", "elif", "else", "fun", "if", "return", "while",]
-        );
+        let keywords: Vec<&str> = vec![
+            "break", "const", "continue", "elif", "else", "enum", "for", "fun", "if", "import",
+            "let", "match", "mod", "return", "struct", "while",
+        ];
+        let code: String = keywords.join(" ");
+        let mut parser: Parser = Parser::init(&code);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), keywords);
+        assert_token_types(&tokens, TokenType::Keyword);
+    }
+
+    #[test]
+    fn test_lexing_match() {
+        let mut parser: Parser = Parser::init("match x { }");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["match", "x", "{", "}"]);
+        assert_eq!(tokens[0].token_type, TokenType::Keyword);
+    }
+
+    #[test]
+    fn test_lexing_import() {
+        let mut parser: Parser = Parser::init("import \"std/io\";");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["import", "\"std/io\"", ";"]);
+        assert_eq!(tokens[0].token_type, TokenType::Keyword);
+        assert_eq!(tokens[1].value, Some("std/io".to_string()));
+    }
+
+    #[test]
+    fn test_lexing_identifiers_starting_with_keywords() {
+        let identifiers: Vec<&str> = vec![
+            "format",
+            "iffy",
+            "funny",
+            "integer",
+            "string",
+            "structure",
+            "enumerate",
+            "lethargy",
+            "constant",
+            "matches",
+            "imported",
+            "module",
+            "floating",
+            "voidptr",
+            "boolean",
+        ];
+        let code: String = identifiers.join(" ");
+        let mut parser: Parser = Parser::init(&code);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), identifiers);
+        assert_token_types(&tokens, TokenType::Identifier);
     }
 
     #[test]
@@ -154,53 +1966,277 @@ mod tests {
 
         let operators: &str = "++ -- ! &";
         let mut parser: Parser = Parser::init(operators);
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["++", "--", "!", "&"]);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["++", "--", "!", "&"]);
+        assert_token_types(&tokens, TokenType::UnaryOperator);
     }
 
     #[test]
     fn test_lexing_binary_operators() {
         let operator_count: usize = count_token_types(TokenType::BinaryOperator);
-        assert_eq!(operator_count, 15, "Exhaustive testing of BinaryOperators");
+        assert_eq!(operator_count, 33, "Exhaustive testing of BinaryOperators");
 
-        let operators: &str = "+ - / * == != >= > <= < = += -= *= /=";
+        let operators: &str =
+            "+ - / * % == != >= > <= < = += -= *= /= %= && || | ^ << >> &= |= ^= <<= >>= .. ..= ** ? ??";
         let mut parser: Parser = Parser::init(operators);
-        let tokens: Vec<&str> = parser.parse();
+        let tokens: Vec<Token> = parser.parse().unwrap();
         assert_eq!(
-            tokens,
+            get_lexemes(&tokens),
             vec![
-                "+", "-", "/", "*", "==", "!=", ">=", ">", "<=", "<", "=", "+=", "-=", "*=", "/=",
+                "+", "-", "/", "*", "%", "==", "!=", ">=", ">", "<=", "<", "=", "+=", "-=", "*=",
+                "/=", "%=", "&&", "||", "|", "^", "<<", ">>", "&=", "|=", "^=", "<<=", ">>=", "..",
+                "..=", "**", "?", "??",
             ]
         );
+        assert_token_types(&tokens, TokenType::BinaryOperator);
+    }
+
+    #[test]
+    fn test_lexing_exponentiation() {
+        let mut parser: Parser = Parser::init("2 ** 10");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["2", "**", "10"]);
+        assert_eq!(tokens[1].token_type, TokenType::BinaryOperator);
+
+        let mut parser: Parser = Parser::init("a *= b; a * b; a**b");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["a", "*=", "b", ";", "a", "*", "b", ";", "a", "**", "b"]
+        );
+    }
+
+    #[test]
+    fn test_lexing_range() {
+        let mut parser: Parser = Parser::init("1..10");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["1", "..", "10"]);
+        assert_eq!(tokens[0].token_type, TokenType::Literal(DataType::Integer));
+        assert_eq!(tokens[1].token_type, TokenType::BinaryOperator);
+        assert_eq!(tokens[2].token_type, TokenType::Literal(DataType::Integer));
+
+        let mut parser: Parser = Parser::init("1.0..2.0");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["1.0", "..", "2.0"]);
+        assert_eq!(tokens[0].token_type, TokenType::Literal(DataType::Float));
+        assert_eq!(tokens[1].token_type, TokenType::BinaryOperator);
+        assert_eq!(tokens[2].token_type, TokenType::Literal(DataType::Float));
+
+        let mut parser: Parser = Parser::init("0..=len");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["0", "..=", "len"]);
+
+        // Three dots mark a variadic parameter instead
+        let mut parser: Parser = Parser::init("args: int...)");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["args", ":", "int", "...", ")"]);
+        assert_eq!(tokens[3].token_type, TokenType::Delimiter);
     }
 
     #[test]
     fn test_lexing_delimiters() {
         let delimiter_count: usize = count_token_types(TokenType::Delimiter);
-        assert_eq!(delimiter_count, 10, "Exhaustive testing of Delimiters");
+        assert_eq!(delimiter_count, 14, "Exhaustive testing of Delimiters");
 
-        let delimiters: &str = "()[]{}->:,;";
+        let delimiters: &str = "()[]{}->:,;... .::=>";
         let mut parser: Parser = Parser::init(delimiters);
-        let tokens: Vec<&str> = parser.parse();
+        let tokens: Vec<Token> = parser.parse().unwrap();
         assert_eq!(
-            tokens,
-            vec!["(", ")", "[", "]", "{", "}", "->", ":", ",", ";",]
+            get_lexemes(&tokens),
+            vec!["(", ")", "[", "]", "{", "}", "->", ":", ",", ";", "...", ".", "::", "=>"]
+        );
+        assert_token_types(&tokens, TokenType::Delimiter);
+    }
+
+    #[test]
+    fn test_lexing_member_access() {
+        let mut parser: Parser = Parser::init("foo.bar.baz");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["foo", ".", "bar", ".", "baz"]);
+        let token_types: Vec<&TokenType> = tokens.iter().map(|token| &token.token_type).collect();
+        assert_eq!(
+            token_types,
+            vec![
+                &TokenType::Identifier,
+                &TokenType::Delimiter,
+                &TokenType::Identifier,
+                &TokenType::Delimiter,
+                &TokenType::Identifier,
+            ]
+        );
+
+        // A point between digits still belongs to the float
+        let mut parser: Parser = Parser::init("point.x = 3.14;");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["point", ".", "x", "=", "3.14", ";"]
         );
     }
 
+    #[test]
+    fn test_lexing_scope_resolution() {
+        let mut parser: Parser = Parser::init("a::b::c");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "::", "b", "::", "c"]);
+        assert_eq!(tokens[1].token_type, TokenType::Delimiter);
+        assert_eq!(tokens[3].span, 4..6);
+
+        let codes: Vec<(&str, Vec<&str>)> = vec![
+            ("Color::Red", vec!["Color", "::", "Red"]),
+            ("a : int", vec!["a", ":", "int"]),
+            ("a: int", vec!["a", ":", "int"]),
+            // Three colons are a path separator and a type annotation
+            ("a:::b", vec!["a", "::", ":", "b"]),
+        ];
+        for (code, lexemes) in codes {
+            let mut parser: Parser = Parser::init(code);
+            assert_eq!(get_lexemes(&parser.parse().unwrap()), lexemes, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_lexing_null_coalescing() {
+        let mut parser: Parser = Parser::init("a ?? none");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "??", "none"]);
+        assert_eq!(tokens[1].token_type, TokenType::BinaryOperator);
+        assert_eq!(tokens[2].token_type, TokenType::Null);
+
+        let mut parser: Parser = Parser::init("a???b:c");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "??", "?", "b", ":", "c"]);
+    }
+
+    #[test]
+    fn test_lexing_fat_arrow() {
+        let mut parser: Parser = Parser::init("x => y");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["x", "=>", "y"]);
+        assert_eq!(tokens[1].token_type, TokenType::Delimiter);
+
+        // Comparisons and assignments keep their own Tokens
+        let codes: Vec<(&str, Vec<&str>, TokenType)> = vec![
+            ("a >= b", vec!["a", ">=", "b"], TokenType::BinaryOperator),
+            ("a = b", vec!["a", "=", "b"], TokenType::BinaryOperator),
+            (
+                "a == >b",
+                vec!["a", "==", ">", "b"],
+                TokenType::BinaryOperator,
+            ),
+            ("a=>=b", vec!["a", "=>", "=", "b"], TokenType::Delimiter),
+        ];
+        for (code, lexemes, token_type) in codes {
+            let mut parser: Parser = Parser::init(code);
+            let tokens: Vec<Token> = parser.parse().unwrap();
+            assert_eq!(get_lexemes(&tokens), lexemes, "{}", code);
+            assert_eq!(tokens[1].token_type, token_type, "{}", code);
+        }
+    }
+
     #[test]
     fn test_lexing_assignment_statement() {
         let mut parser: Parser = Parser::init("a += 42;");
-        let tokens: Vec<&str> = parser.parse();
-        assert_eq!(tokens, vec!["a", "+=", "42", ";",]);
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(get_lexemes(&tokens), vec!["a", "+=", "42", ";",]);
+    }
+
+    #[test]
+    fn test_lexing_remainder() {
+        let mut parser: Parser = Parser::init("a % b; a %= b;");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["a", "%", "b", ";", "a", "%=", "b", ";"]
+        );
+    }
+
+    #[test]
+    fn test_lexing_logical_operators() {
+        let mut parser: Parser = Parser::init("a && b || c &d");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["a", "&&", "b", "||", "c", "&", "d"]
+        );
+        assert_eq!(tokens[1].token_type, TokenType::BinaryOperator);
+        assert_eq!(tokens[5].token_type, TokenType::UnaryOperator);
+    }
+
+    #[test]
+    fn test_lexing_bitwise_operators() {
+        let mut parser: Parser = Parser::init("a << 2 | b & c ^ d >> 1 <= e");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["a", "<<", "2", "|", "b", "&", "c", "^", "d", ">>", "1", "<=", "e"]
+        );
+    }
+
+    #[test]
+    fn test_lexing_bitwise_assignment() {
+        let mut parser: Parser = Parser::init("a <<= 3; b &= mask;");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            get_lexemes(&tokens),
+            vec!["a", "<<=", "3", ";", "b", "&=", "mask", ";"]
+        );
+        assert_eq!(tokens[5].token_type, TokenType::BinaryOperator);
+    }
+
+    #[test]
+    fn test_lexing_token_spans() {
+        let mut parser: Parser = Parser::init("if !done");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Keyword,
+                    lexeme: Lexeme::Symbol(intern("if")),
+                    span: 0..2,
+                    line: 1,
+                    column: 1,
+                    value: None,
+                },
+                Token {
+                    token_type: TokenType::UnaryOperator,
+                    lexeme: Lexeme::Text("!".to_string()),
+                    span: 3..4,
+                    line: 1,
+                    column: 4,
+                    value: None,
+                },
+                Token {
+                    token_type: TokenType::Identifier,
+                    lexeme: Lexeme::Symbol(intern("done")),
+                    span: 4..8,
+                    line: 1,
+                    column: 5,
+                    value: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexing_line_numbers() {
+        let mut parser: Parser = Parser::init("a\n  b");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!((tokens[1].line, tokens[1].column), (2, 3));
+
+        // Block comments advance the line and '\r\n' is a single line break
+        let mut parser: Parser = Parser::init("/* one\ntwo */\r\n\r\n c");
+        let tokens: Vec<Token> = parser.parse().unwrap();
+        assert_eq!((tokens[0].line, tokens[0].column), (4, 2));
     }
 
     #[test]
     fn test_lexing_if_else() {
         let mut parser: Parser = Parser::init("if a==b { a++; } else { --a; }");
-        let tokens: Vec<&str> = parser.parse();
+        let tokens: Vec<Token> = parser.parse().unwrap();
         assert_eq!(
-            tokens,
+            get_lexemes(&tokens),
             vec!["if", "a", "==", "b", "{", "a", "++", ";", "}", "else", "{", "--", "a", ";", "}",]
         );
     }
@@ -1,29 +1,208 @@
+// Explicit returns and field names are the house style
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
 mod ast;
 mod defs;
+mod diagnostics;
+mod intern;
+mod interpreter;
 mod lexer;
+mod opt;
+mod pretty;
+mod typecheck;
 mod utils;
 
-use ast::generate_ast;
-use defs::Program;
+use ast::{generate_ast, generate_ast_recover};
+use defs::{LexError, ParseError, Program, RuntimeError, Token, TypeError};
+use diagnostics::render_diagnostic;
+use interpreter::{exec_program, Env};
+use std::env;
 use std::fs;
+use std::io::{self, Write};
+use std::process;
 
 use lexer::Parser;
+use pretty::pretty;
+use typecheck::check_program;
+use utils::token_stats;
 fn main() {
-    // TODO: Parse command line arguments
+    // '--tokens' and '--ast-json' dump the Tokens or the AST as JSON instead of pretty printing
+    // '--stats' prints how many Tokens of each type a file has
+    // '--run' interprets the file after type checking it instead of pretty printing it
+    let args: Vec<String> = env::args().skip(1).collect();
+    let dump_tokens: bool = args.iter().any(|arg| arg == "--tokens");
+    let dump_ast: bool = args.iter().any(|arg| arg == "--ast-json");
+    let dump_stats: bool = args.iter().any(|arg| arg == "--stats");
+    let interpret: bool = args.iter().any(|arg| arg == "--run");
+    match args.iter().find(|arg| !arg.starts_with("--")) {
+        None => run_repl(dump_tokens),
+        Some(path) if path == "repl" => run_repl(dump_tokens),
+        Some(path) => run_file(path, dump_tokens, dump_ast, dump_stats, interpret),
+    }
+}
 
+fn run_file(path: &str, dump_tokens: bool, dump_ast: bool, dump_stats: bool, interpret: bool) {
     // Read code files
     // TODO: Enable including code from multiple files
-    let code: String = fs::read_to_string("test.pog").expect("Failed to read the file");
+    let code: Result<String, io::Error> = match path {
+        "-" => io::read_to_string(io::stdin()),
+        _ => fs::read_to_string(path),
+    };
+    let code: String = match code {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Failed to read '{}': {}", path, error);
+            process::exit(1);
+        }
+    };
 
-    // Parse code to Tokens
+    // Parse code to Tokens, reporting every error found
     let mut parser: Parser = Parser::init(code.as_str());
-    let tokens: Vec<&str> = parser.parse();
+    let (tokens, errors): (Vec<Token>, Vec<LexError>) = parser.parse_recover();
+    for error in errors.iter() {
+        eprintln!(
+            "{}",
+            render_diagnostic(&code, &error.to_string(), error.span())
+        );
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+
+    if dump_tokens {
+        print_tokens(&tokens);
+        return;
+    }
+    if dump_stats {
+        print_stats(&tokens);
+        return;
+    }
+
+    // Generate abstract syntax tree (AST), reporting every error found
+    let (program, errors): (Program, Vec<ParseError>) = generate_ast_recover(&tokens);
+    for error in errors.iter() {
+        eprintln!("{}", render_parse_error(&code, error));
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
 
-    // Generate abstract syntax tree (AST)
-    let program: Program = generate_ast(&tokens);
-    dbg!(&program);
+    if dump_ast {
+        print_ast(&program);
+        return;
+    }
+
+    // Type check Program, reporting every error found
+    let errors: Vec<TypeError> = check_program(&program);
+    for error in errors.iter() {
+        eprintln!(
+            "{}",
+            render_diagnostic(&code, &error.message, error.span.clone())
+        );
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+
+    if interpret {
+        run_program(&code, &program);
+        return;
+    }
+    print!("{}", pretty(&program));
 
-    // TODO: Type check Program
     // TODO: Generate assembly code
     // TODO: Compile executable
 }
+
+// Interpret the Program, print functions writing to stdout
+fn run_program(code: &str, program: &Program) {
+    let mut env: Env = Env::init();
+    let result: Result<(), RuntimeError> = exec_program(program, &mut env);
+    io::stdout().flush().expect("Failed to flush stdout");
+    if let Err(error) = result {
+        eprintln!(
+            "{}",
+            render_diagnostic(code, &error.to_string(), error.span())
+        );
+        process::exit(1);
+    }
+}
+
+// Parse the code line by line until EOF, printing errors without stopping
+fn run_repl(dump_tokens: bool) {
+    let mut lines = io::stdin().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().expect("Failed to flush stdout");
+        let line: String = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(error)) => {
+                eprintln!("Failed to read stdin: {}", error);
+                process::exit(1);
+            }
+            None => return,
+        };
+
+        let mut parser: Parser = Parser::init(line.as_str());
+        let tokens: Vec<Token> = match parser.parse() {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                eprintln!(
+                    "{}",
+                    render_diagnostic(&line, &error.to_string(), error.span())
+                );
+                continue;
+            }
+        };
+
+        if dump_tokens {
+            print_tokens(&tokens);
+            continue;
+        }
+
+        match generate_ast(&tokens) {
+            Ok(program) => print!("{}", pretty(&program)),
+            Err(error) => eprintln!("{}", render_parse_error(&line, &error)),
+        }
+    }
+}
+
+// Most common TokenTypes first, ties in alphabetical order
+fn print_stats(tokens: &[Token]) {
+    let mut stats: Vec<(String, usize)> = token_stats(tokens)
+        .into_iter()
+        .map(|(token_type, count)| (token_type.to_string(), count))
+        .collect();
+    stats.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    for (token_type, count) in stats {
+        println!("{}: {}", token_type, count);
+    }
+}
+
+fn render_parse_error(code: &str, error: &ParseError) -> String {
+    return render_diagnostic(code, &error.message, error.span.clone());
+}
+
+#[cfg(feature = "serde")]
+fn print_tokens(tokens: &[Token]) {
+    let json: String = serde_json::to_string(tokens).expect("Tokens are always serializable");
+    println!("{}", json);
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_tokens(_tokens: &[Token]) {
+    eprintln!("Dumping Tokens requires building pog with the 'serde' feature");
+    process::exit(1);
+}
+
+#[cfg(feature = "serde")]
+fn print_ast(program: &Program) {
+    let json: String = serde_json::to_string(program).expect("Programs are always serializable");
+    println!("{}", json);
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_ast(_program: &Program) {
+    eprintln!("Dumping the AST requires building pog with the 'serde' feature");
+    process::exit(1);
+}
@@ -0,0 +1,246 @@
+use crate::ast::ASSIGNMENT_OPERATORS;
+use crate::defs::{
+    ArmBody, DataType, Expr, ExprKind, FnDecl, MatchArm, Program, RuntimeError, Stmt, StmtKind,
+    Value, VarDecl,
+};
+use crate::interpreter::{eval_expr, Env};
+
+// Return a copy of the Program where Expressions made only of literals are replaced by their
+// value, e.g. '2 + 3 * 4' becomes '14'
+// Expressions that fail at runtime, like '1 / 0', are kept so the error is still reported
+#[allow(dead_code)]
+pub(crate) fn fold_constants(program: &Program) -> Program {
+    return Program {
+        statements: fold_block(&program.statements),
+    };
+}
+
+fn fold_block(body: &[Stmt]) -> Vec<Stmt> {
+    return body.iter().map(fold_statement).collect();
+}
+
+fn fold_statement(statement: &Stmt) -> Stmt {
+    let kind: StmtKind = match &statement.kind {
+        StmtKind::Block(body) => StmtKind::Block(fold_block(body)),
+        StmtKind::Expression(expression) => StmtKind::Expression(fold_expression(expression)),
+        StmtKind::FnDecl(function) => StmtKind::FnDecl(FnDecl {
+            body: fold_block(&function.body),
+            ..function.clone()
+        }),
+        StmtKind::If {
+            condition,
+            body,
+            elifs,
+            else_body,
+        } => StmtKind::If {
+            condition: fold_expression(condition),
+            body: fold_block(body),
+            elifs: elifs
+                .iter()
+                .map(|(condition, body)| (fold_expression(condition), fold_block(body)))
+                .collect(),
+            else_body: else_body.as_deref().map(fold_block),
+        },
+        StmtKind::For {
+            init,
+            condition,
+            step,
+            body,
+        } => StmtKind::For {
+            init: init.as_deref().map(|init| Box::new(fold_statement(init))),
+            condition: condition.as_ref().map(fold_expression),
+            step: step.as_ref().map(fold_expression),
+            body: fold_block(body),
+        },
+        StmtKind::ForIn {
+            variable,
+            iterable,
+            body,
+        } => StmtKind::ForIn {
+            variable: variable.clone(),
+            iterable: fold_expression(iterable),
+            body: fold_block(body),
+        },
+        StmtKind::Return(value) => StmtKind::Return(value.as_ref().map(fold_expression)),
+        StmtKind::VarDecl(declaration) => StmtKind::VarDecl(VarDecl {
+            value: declaration.value.as_ref().map(fold_expression),
+            ..declaration.clone()
+        }),
+        StmtKind::While { condition, body } => StmtKind::While {
+            condition: fold_expression(condition),
+            body: fold_block(body),
+        },
+        kind => kind.clone(),
+    };
+    return Stmt::init(kind, statement.span());
+}
+
+// Children are folded first so the folding can continue upwards
+fn fold_expression(expression: &Expr) -> Expr {
+    let kind: ExprKind = match &expression.kind {
+        ExprKind::Unary { operator, operand } => ExprKind::Unary {
+            operator: operator.clone(),
+            operand: Box::new(fold_expression(operand)),
+        },
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } => ExprKind::Binary {
+            operator: operator.clone(),
+            left: Box::new(fold_expression(left)),
+            right: Box::new(fold_expression(right)),
+        },
+        ExprKind::Ternary {
+            condition,
+            then_value,
+            else_value,
+        } => ExprKind::Ternary {
+            condition: Box::new(fold_expression(condition)),
+            then_value: Box::new(fold_expression(then_value)),
+            else_value: Box::new(fold_expression(else_value)),
+        },
+        ExprKind::Grouping(expression) => ExprKind::Grouping(Box::new(fold_expression(expression))),
+        // Calls can have side effects so only their arguments are folded
+        ExprKind::Call { callee, arguments } => ExprKind::Call {
+            callee: callee.clone(),
+            arguments: arguments.iter().map(fold_expression).collect(),
+        },
+        ExprKind::ArrayLit(elements) => {
+            ExprKind::ArrayLit(elements.iter().map(fold_expression).collect())
+        }
+        ExprKind::Index { target, index } => ExprKind::Index {
+            target: Box::new(fold_expression(target)),
+            index: Box::new(fold_expression(index)),
+        },
+        ExprKind::StructLit { name, fields } => ExprKind::StructLit {
+            name: name.clone(),
+            fields: fields
+                .iter()
+                .map(|(field, value)| (field.clone(), fold_expression(value)))
+                .collect(),
+        },
+        ExprKind::FieldAccess { target, field } => ExprKind::FieldAccess {
+            target: Box::new(fold_expression(target)),
+            field: field.clone(),
+        },
+        // Patterns are already constant
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: Box::new(fold_expression(scrutinee)),
+            arms: arms
+                .iter()
+                .map(|arm| MatchArm {
+                    pattern: arm.pattern.clone(),
+                    body: match &arm.body {
+                        ArmBody::Block(body) => ArmBody::Block(fold_block(body)),
+                        ArmBody::Expression(expression) => {
+                            ArmBody::Expression(fold_expression(expression))
+                        }
+                    },
+                })
+                .collect(),
+        },
+        // Literals and identifiers have nothing to fold, increments change a variable and lambdas
+        // are only folded when called
+        kind => return Expr::init(kind.clone(), expression.span()),
+    };
+    let folded: Expr = Expr::init(kind, expression.span());
+    if !is_constant(&folded) {
+        return folded;
+    }
+    let value: Result<Value, RuntimeError> = eval_expr(&folded, &mut Env::init());
+    match value.ok().and_then(|value| get_literal(&value)) {
+        Some(literal) => Expr::init(literal, expression.span()),
+        None => folded,
+    }
+}
+
+// Operators whose operands are all literals, assignments are never constant
+fn is_constant(expression: &Expr) -> bool {
+    match &expression.kind {
+        ExprKind::Unary { operand, .. } => is_literal(operand),
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } => {
+            !ASSIGNMENT_OPERATORS.contains(&operator.as_str())
+                && is_literal(left)
+                && is_literal(right)
+        }
+        ExprKind::Grouping(expression) => is_literal(expression),
+        _ => false,
+    }
+}
+
+fn is_literal(expression: &Expr) -> bool {
+    return matches!(expression.kind, ExprKind::Literal { .. });
+}
+
+// Arrays have no literal and non-finite floats can not be written in code
+fn get_literal(value: &Value) -> Option<ExprKind> {
+    let (typ, value): (DataType, String) = match value {
+        Value::Boolean(value) => (DataType::Boolean, value.to_string()),
+        Value::Character(value) => (DataType::Character, value.to_string()),
+        Value::Float(value) if value.is_finite() => (DataType::Float, format!("{:?}", value)),
+        Value::Integer(value) => (DataType::Integer, value.to_string()),
+        Value::None => (DataType::None, "none".to_string()),
+        Value::String(value) => (DataType::String, value.clone()),
+        _ => return None,
+    };
+    return Some(ExprKind::Literal {
+        typ: typ,
+        value: value,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::generate_ast;
+    use crate::lexer::Parser;
+    use crate::pretty::pretty;
+
+    fn fold_from(code: &str) -> String {
+        let mut parser: Parser = Parser::init(code);
+        let program: Program = generate_ast(&parser.parse().unwrap()).unwrap();
+        return pretty(&fold_constants(&program));
+    }
+
+    #[test]
+    fn test_folding_constants() {
+        assert_eq!(fold_from("2 + 3 * 4;"), "14;\n");
+        assert_eq!(fold_from("true && false;"), "false;\n");
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("-(1 + 2) ** 2;", "-9;\n"),
+            ("1.5 * 2.0;", "3.0;\n"),
+            ("\"a\" + \"b\" == \"ab\";", "true;\n"),
+            ("[1 + 1, x * (2 - 1)][0];", "[2, (x * 1)][0];\n"),
+            ("let y = x + 2 * 3;", "let y = (x + 6);\n"),
+            (
+                "while 1 < 2 { return !false; }",
+                "while true {\n    return true;\n}\n",
+            ),
+        ];
+        for (code, folded) in expressions {
+            assert_eq!(fold_from(code), folded, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_folding_keeps_non_constants() {
+        // Calls are never folded, only their arguments
+        assert_eq!(fold_from("f(1 + 2) + 3;"), "(f(3) + 3);\n");
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("x = 1 + 2;", "(x = 3);\n"),
+            ("1 / 0;", "(1 / 0);\n"),
+            ("++x;", "(++x);\n"),
+            ("x += 2 * 3;", "(x += 6);\n"),
+        ];
+        for (code, folded) in expressions {
+            assert_eq!(fold_from(code), folded, "{}", code);
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use crate::defs::{
+    get_for_header, ArmBody, DataType, Expr, ExprKind, FnDecl, LambdaBody, Parameter, Pattern,
+    Program, Stmt, StmtKind, VarDecl,
+};
+
+const INDENT: &str = "    ";
+
+// Render the Program like source code with one Statement per line, e.g.
+// fun main() {
+//     let x = (1 + (2 * 3));
+// }
+// Expressions are fully parenthesized to show how they were nested
+pub(crate) fn pretty(program: &Program) -> String {
+    let mut output: String = String::new();
+    for statement in program.statements.iter() {
+        write_statement(&mut output, statement, 0);
+    }
+    return output;
+}
+
+fn write_statement(output: &mut String, statement: &Stmt, depth: usize) {
+    output.push_str(&INDENT.repeat(depth));
+    match &statement.kind {
+        StmtKind::Block(body) => write_block(output, body, depth),
+        StmtKind::Break => output.push_str("break;"),
+        StmtKind::Continue => output.push_str("continue;"),
+        StmtKind::Expression(expression) => output.push_str(&format!("{};", expression)),
+        StmtKind::FnDecl(function) => write_function(output, function, depth),
+        StmtKind::If {
+            condition,
+            body,
+            elifs,
+            else_body,
+        } => {
+            write_branch(output, "if", condition, body, depth);
+            for (condition, body) in elifs.iter() {
+                write_branch(output, " elif", condition, body, depth);
+            }
+            if let Some(body) = else_body {
+                output.push_str(" else ");
+                write_block(output, body, depth);
+            }
+        }
+        StmtKind::NoOperation => output.push(';'),
+        StmtKind::Return(None) => output.push_str("return;"),
+        StmtKind::Return(Some(expression)) => output.push_str(&format!("return {};", expression)),
+        StmtKind::EnumDecl(_) | StmtKind::StructDecl(_) => output.push_str(&statement.to_string()),
+        StmtKind::VarDecl(declaration) => write_declaration(output, declaration),
+        StmtKind::While { condition, body } => {
+            write_branch(output, "while", condition, body, depth)
+        }
+        StmtKind::ForIn {
+            variable,
+            iterable,
+            body,
+        } => {
+            output.push_str(&format!("for {} in {} ", variable, iterable));
+            write_block(output, body, depth);
+        }
+        StmtKind::For {
+            init,
+            condition,
+            step,
+            body,
+        } => {
+            output.push_str(&format!("{} ", get_for_header(init, condition, step)));
+            write_block(output, body, depth);
+        }
+    }
+    output.push('\n');
+}
+
+// Empty blocks stay on one line, others end with the closing bracket on its own line
+fn write_block(output: &mut String, body: &[Stmt], depth: usize) {
+    if body.is_empty() {
+        output.push_str("{}");
+        return;
+    }
+    output.push_str("{\n");
+    for statement in body.iter() {
+        write_statement(output, statement, depth + 1);
+    }
+    output.push_str(&INDENT.repeat(depth));
+    output.push('}');
+}
+
+fn write_branch(output: &mut String, keyword: &str, condition: &Expr, body: &[Stmt], depth: usize) {
+    output.push_str(&format!("{} {} ", keyword, condition));
+    write_block(output, body, depth);
+}
+
+fn write_function(output: &mut String, function: &FnDecl, depth: usize) {
+    let parameters: Vec<String> = function
+        .parameters
+        .iter()
+        .map(Parameter::to_string)
+        .collect();
+    output.push_str(&format!(
+        "fun {}({}) ",
+        function.name,
+        parameters.join(", ")
+    ));
+    if function.return_type != DataType::None {
+        output.push_str(&format!("-> {} ", function.return_type));
+    }
+    write_block(output, &function.body, depth);
+}
+
+fn write_declaration(output: &mut String, declaration: &VarDecl) {
+    let keyword: &str = match declaration.mutable {
+        true => "let",
+        false => "const",
+    };
+    output.push_str(&format!("{} {}", keyword, declaration.name));
+    if declaration.typ != DataType::None {
+        output.push_str(&format!(": {}", declaration.typ));
+    }
+    if let Some(value) = &declaration.value {
+        output.push_str(&format!(" = {}", value));
+    }
+    output.push(';');
+}
+
+// Render the Program as S-expressions with one Statement per line, e.g.
+// (let x (+ 1 (* 2 3)))
+#[allow(dead_code)]
+pub(crate) fn to_sexpr(program: &Program) -> String {
+    let statements: Vec<String> = program.statements.iter().map(stmt_to_sexpr).collect();
+    return statements.join("\n");
+}
+
+fn stmt_to_sexpr(statement: &Stmt) -> String {
+    match &statement.kind {
+        StmtKind::Block(body) => block_to_sexpr(body),
+        StmtKind::Break => "(break)".to_string(),
+        StmtKind::Continue => "(continue)".to_string(),
+        StmtKind::EnumDecl(declaration) => {
+            let items = [declaration.name.clone()].into_iter();
+            get_sexpr("enum", items.chain(declaration.variants.iter().cloned()))
+        }
+        StmtKind::Expression(expression) => expr_to_sexpr(expression),
+        StmtKind::FnDecl(function) => {
+            let mut items: Vec<String> = vec![function.name.clone()];
+            items.push(parameters_to_sexpr(&function.parameters));
+            if function.return_type != DataType::None {
+                items.push(function.return_type.to_string());
+            }
+            items.push(block_to_sexpr(&function.body));
+            get_sexpr("fun", items.into_iter())
+        }
+        StmtKind::If {
+            condition,
+            body,
+            elifs,
+            else_body,
+        } => {
+            let mut items: Vec<String> = vec![expr_to_sexpr(condition), block_to_sexpr(body)];
+            for (condition, body) in elifs.iter() {
+                let branch: [String; 2] = [expr_to_sexpr(condition), block_to_sexpr(body)];
+                items.push(get_sexpr("elif", branch.into_iter()));
+            }
+            if let Some(body) = else_body {
+                items.push(get_sexpr("else", [block_to_sexpr(body)].into_iter()));
+            }
+            get_sexpr("if", items.into_iter())
+        }
+        StmtKind::NoOperation => "(nop)".to_string(),
+        StmtKind::Return(value) => get_sexpr("return", value.iter().map(expr_to_sexpr)),
+        StmtKind::StructDecl(declaration) => {
+            let fields = declaration
+                .fields
+                .iter()
+                .map(|field| format!("({} {})", field.name, field.typ));
+            let items = [declaration.name.clone()].into_iter().chain(fields);
+            get_sexpr("struct", items)
+        }
+        StmtKind::VarDecl(declaration) => {
+            // Typed declarations are written as (let (x int) 1)
+            let keyword: &str = match declaration.mutable {
+                true => "let",
+                false => "const",
+            };
+            let mut items: Vec<String> = vec![match declaration.typ {
+                DataType::None => declaration.name.clone(),
+                _ => format!("({} {})", declaration.name, declaration.typ),
+            }];
+            items.extend(declaration.value.iter().map(expr_to_sexpr));
+            get_sexpr(keyword, items.into_iter())
+        }
+        StmtKind::While { condition, body } => {
+            let items: [String; 2] = [expr_to_sexpr(condition), block_to_sexpr(body)];
+            get_sexpr("while", items.into_iter())
+        }
+        StmtKind::ForIn {
+            variable,
+            iterable,
+            body,
+        } => {
+            let items: [String; 3] = [
+                variable.clone(),
+                expr_to_sexpr(iterable),
+                block_to_sexpr(body),
+            ];
+            get_sexpr("for-in", items.into_iter())
+        }
+        // Empty clauses are written as ()
+        StmtKind::For {
+            init,
+            condition,
+            step,
+            body,
+        } => {
+            let items: [String; 4] = [
+                init.as_deref().map_or("()".to_string(), stmt_to_sexpr),
+                condition.as_ref().map_or("()".to_string(), expr_to_sexpr),
+                step.as_ref().map_or("()".to_string(), expr_to_sexpr),
+                block_to_sexpr(body),
+            ];
+            get_sexpr("for", items.into_iter())
+        }
+    }
+}
+
+// Parameters are written as (a int), (b int 10) or (c int...)
+fn parameters_to_sexpr(parameters: &[Parameter]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|parameter| {
+            let typ: Option<String> = match (&parameter.typ, parameter.variadic) {
+                (DataType::None, _) => None,
+                (typ, true) => Some(format!("{}...", typ)),
+                (typ, false) => Some(typ.to_string()),
+            };
+            let default = parameter.default.iter().map(expr_to_sexpr);
+            let items = [parameter.name.clone()]
+                .into_iter()
+                .chain(typ)
+                .chain(default);
+            let items: Vec<String> = items.collect();
+            format!("({})", items.join(" "))
+        })
+        .collect();
+    return format!("({})", parameters.join(" "));
+}
+
+fn block_to_sexpr(body: &[Stmt]) -> String {
+    return get_sexpr("block", body.iter().map(stmt_to_sexpr));
+}
+
+fn expr_to_sexpr(expression: &Expr) -> String {
+    match &expression.kind {
+        // Literals and identifiers are written like in Expr's Display
+        ExprKind::Literal { .. } | ExprKind::Identifier(_) => expression.to_string(),
+        ExprKind::Unary { operator, operand } => {
+            get_sexpr(operator, [expr_to_sexpr(operand)].into_iter())
+        }
+        ExprKind::PreInc(operand) => get_sexpr("pre++", [expr_to_sexpr(operand)].into_iter()),
+        ExprKind::PreDec(operand) => get_sexpr("pre--", [expr_to_sexpr(operand)].into_iter()),
+        ExprKind::PostInc(operand) => get_sexpr("post++", [expr_to_sexpr(operand)].into_iter()),
+        ExprKind::PostDec(operand) => get_sexpr("post--", [expr_to_sexpr(operand)].into_iter()),
+        ExprKind::Binary {
+            operator,
+            left,
+            right,
+        } => get_sexpr(
+            operator,
+            [expr_to_sexpr(left), expr_to_sexpr(right)].into_iter(),
+        ),
+        ExprKind::Ternary {
+            condition,
+            then_value,
+            else_value,
+        } => {
+            let items = [condition, then_value, else_value].map(|item| expr_to_sexpr(item));
+            get_sexpr("?", items.into_iter())
+        }
+        ExprKind::Grouping(expression) => {
+            get_sexpr("group", [expr_to_sexpr(expression)].into_iter())
+        }
+        ExprKind::Call { callee, arguments } => {
+            let items = [expr_to_sexpr(callee)]
+                .into_iter()
+                .chain(arguments.iter().map(expr_to_sexpr));
+            get_sexpr("call", items)
+        }
+        ExprKind::ArrayLit(elements) => get_sexpr("array", elements.iter().map(expr_to_sexpr)),
+        ExprKind::Index { target, index } => get_sexpr(
+            "index",
+            [expr_to_sexpr(target), expr_to_sexpr(index)].into_iter(),
+        ),
+        ExprKind::StructLit { name, fields } => {
+            let fields = fields
+                .iter()
+                .map(|(field, value)| format!("({} {})", field, expr_to_sexpr(value)));
+            get_sexpr("new", [name.clone()].into_iter().chain(fields))
+        }
+        ExprKind::FieldAccess { target, field } => {
+            get_sexpr(".", [expr_to_sexpr(target), field.clone()].into_iter())
+        }
+        ExprKind::EnumVariant { name, variant } => {
+            get_sexpr("::", [name.clone(), variant.clone()].into_iter())
+        }
+        // Arms are written as (pattern body), e.g. (_ (block))
+        ExprKind::Match { scrutinee, arms } => {
+            let arms = arms.iter().map(|arm| {
+                let pattern: String = match &arm.pattern {
+                    Pattern::Wildcard => "_".to_string(),
+                    Pattern::Value(value) => expr_to_sexpr(value),
+                };
+                let body: String = match &arm.body {
+                    ArmBody::Block(body) => block_to_sexpr(body),
+                    ArmBody::Expression(expression) => expr_to_sexpr(expression),
+                };
+                format!("({} {})", pattern, body)
+            });
+            get_sexpr("match", [expr_to_sexpr(scrutinee)].into_iter().chain(arms))
+        }
+        ExprKind::Lambda {
+            parameters,
+            return_type,
+            body,
+        } => {
+            let mut items: Vec<String> = vec![parameters_to_sexpr(parameters)];
+            if *return_type != DataType::None {
+                items.push(return_type.to_string());
+            }
+            items.push(match body {
+                LambdaBody::Block(body) => block_to_sexpr(body),
+                LambdaBody::Expression(expression) => expr_to_sexpr(expression),
+            });
+            get_sexpr("lambda", items.into_iter())
+        }
+    }
+}
+
+fn get_sexpr(head: &str, items: impl Iterator<Item = String>) -> String {
+    let mut sexpr: String = format!("({}", head);
+    for item in items {
+        sexpr.push(' ');
+        sexpr.push_str(&item);
+    }
+    sexpr.push(')');
+    return sexpr;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::generate_ast;
+    use crate::lexer::Parser;
+
+    fn generate_ast_from(code: &str) -> Program {
+        let mut parser: Parser = Parser::init(code);
+        return generate_ast(&parser.parse().unwrap()).unwrap();
+    }
+
+    fn pretty_from(code: &str) -> String {
+        return pretty(&generate_ast_from(code));
+    }
+
+    fn to_sexpr_from(code: &str) -> String {
+        return to_sexpr(&generate_ast_from(code));
+    }
+
+    #[test]
+    fn test_pretty_program() {
+        let code: &str = "fun sum(int a, int b) -> int { return a+b*2; }
+            fun main() { int c = sum(34,35); if (c == 69) { print(\"Nice\"); } elif c { } }";
+        assert_eq!(
+            pretty_from(code),
+            "fun sum(a: int, b: int) -> int {
+    return (a + (b * 2));
+}
+fun main() {
+    let c: int = sum(34, 35);
+    if ((c == 69)) {
+        print(\"Nice\");
+    } elif c {}
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_pretty_statements() {
+        let statements: Vec<(&str, &str)> = vec![
+            (";", ";\n"),
+            ("{ { break; } }", "{\n    {\n        break;\n    }\n}\n"),
+            (
+                "while i < 10 { i++; continue; }",
+                "while (i < 10) {\n    (i++);\n    continue;\n}\n",
+            ),
+            (
+                "if a {} else { return; }",
+                "if a {} else {\n    return;\n}\n",
+            ),
+            (
+                "let y; const z: str = 'c';",
+                "let y;\nconst z: str = 'c';\n",
+            ),
+            (
+                "fun f(a: int, b: float = -1.5) {}",
+                "fun f(a: int, b: float = (-1.5)) {}\n",
+            ),
+            ("fun f(args: int...) {}", "fun f(args: int...) {}\n"),
+            (
+                "for (let i = 0; i < 3; i++) { f(i); }",
+                "for (let i = 0; (i < 3); (i++)) {\n    f(i);\n}\n",
+            ),
+            ("for (;;) {}", "for (;;) {}\n"),
+            ("for x in 0..3 {}", "for x in (0 .. 3) {}\n"),
+            (
+                "struct P { x: int, y: int } let p = P { x: 1, y: 2 }.x;",
+                "struct P { x: int, y: int }\nlet p = P { x: 1, y: 2 }.x;\n",
+            ),
+        ];
+        for (code, rendered) in statements {
+            assert_eq!(pretty_from(code), rendered, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_sexpr_expressions() {
+        assert_eq!(to_sexpr_from("1 + 2 * 3;"), "(+ 1 (* 2 3))");
+
+        let expressions: Vec<(&str, &str)> = vec![
+            ("true; 1.5; 'c'; \"str\";", "true\n1.5\n'c'\n\"str\""),
+            ("-a ** 2;", "(- (** a 2))"),
+            ("!(a && b);", "(! (group (&& a b)))"),
+            ("++a + b--;", "(+ (pre++ a) (post-- b))"),
+            ("f(); g(1, h(x));", "(call f)\n(call g 1 (call h x))"),
+            ("[1, [2]][0];", "(index (array 1 (array 2)) 0)"),
+            ("a = b += 1;", "(= a (+= b 1))"),
+            ("|| 42;", "(lambda () 42)"),
+            ("a ? b : c ? d : e;", "(? a b (? c d e))"),
+            ("P { x: 1 + 2, y: a.b };", "(new P (x (+ 1 2)) (y (. a b)))"),
+            ("c == Color::Red;", "(== c (:: Color Red))"),
+            (
+                "match x { 1 => a, _ => { b; } };",
+                "(match x (1 a) (_ (block b)))",
+            ),
+            (
+                "|a: int, b| -> int { a + b };",
+                "(lambda ((a int) (b)) int (block (return (+ a b))))",
+            ),
+        ];
+        for (code, sexpr) in expressions {
+            assert_eq!(to_sexpr_from(code), sexpr, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_sexpr_statements() {
+        let code: &str = "fun sum(a: int, b: int) -> int { return a + b; }
+            fun main() { let x; const y: int = 1; { ; } }";
+        assert_eq!(
+            to_sexpr_from(code),
+            "(fun sum ((a int) (b int)) int (block (return (+ a b))))
+(fun main () (block (let x) (const (y int) 1) (block (nop))))"
+        );
+        assert_eq!(
+            to_sexpr_from("fun f(a: int, b: int = 1 + 2) {}"),
+            "(fun f ((a int) (b int (+ 1 2))) (block))"
+        );
+        assert_eq!(
+            to_sexpr_from("fun f(args: str...) {}"),
+            "(fun f ((args str...)) (block))"
+        );
+        assert_eq!(
+            to_sexpr_from("struct P { x: int, y: [str] }"),
+            "(struct P (x int) (y [str]))"
+        );
+        assert_eq!(
+            to_sexpr_from("enum Color { Red, Green }"),
+            "(enum Color Red Green)"
+        );
+
+        assert_eq!(
+            to_sexpr_from("for (let i = 0;; i++) {} for (;;) {}"),
+            "(for (let i 0) () (post++ i) (block))\n(for () () () (block))"
+        );
+
+        let code: &str = "if a { return; } elif b { break; } else { continue; } while c {}";
+        assert_eq!(
+            to_sexpr_from(code),
+            "(if a (block (return)) (elif b (block (break))) (else (block (continue))))
+(while c (block))"
+        );
+    }
+}
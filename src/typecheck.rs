@@ -0,0 +1,782 @@
+use crate::ast::ASSIGNMENT_OPERATORS;
+use crate::defs::{
+    ArmBody, ArrayType, DataType, Expr, ExprKind, LambdaBody, Parameter, Pattern, Program, Stmt,
+    StmtKind, TypeError, VarDecl,
+};
+use crate::utils::get_arity;
+use std::collections::HashMap;
+use std::ops::Range;
+
+// Check the types of every Statement in the Program, reporting all errors found
+// DataType::None is used for values whose type is not known, e.g. undefined variables and
+// arrays, and they are not reported so that unknown code does not cause follow-up errors
+pub(crate) fn check_program(program: &Program) -> Vec<TypeError> {
+    let mut checker: Checker = Checker::init();
+    // Functions can be called before they are declared
+    for statement in program.statements.iter() {
+        if let StmtKind::FnDecl(function) = &statement.kind {
+            checker.define_function(&function.name, &function.parameters, &function.return_type);
+        }
+    }
+    for statement in program.statements.iter() {
+        checker.check_statement(statement);
+    }
+    return checker.errors;
+}
+
+// Signature of a Function, its parameters and return type
+struct Signature {
+    parameters: Vec<Parameter>,
+    return_type: DataType,
+}
+
+struct Checker {
+    // Types of the variables in each scope, innermost scope last
+    scopes: Vec<HashMap<String, DataType>>,
+    functions: HashMap<String, Signature>,
+    // Return type of the Function being checked
+    return_type: Option<DataType>,
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn init() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            return_type: None,
+            errors: vec![],
+        }
+    }
+
+    fn error(&mut self, message: String, span: Range<usize>) {
+        self.errors.push(TypeError {
+            message: message,
+            span: span,
+        });
+    }
+
+    fn define_function(&mut self, name: &str, parameters: &[Parameter], return_type: &DataType) {
+        // Functions without a return type return void
+        let return_type: DataType = match return_type {
+            DataType::None => DataType::Void,
+            typ => typ.clone(),
+        };
+        let signature: Signature = Signature {
+            parameters: parameters.to_vec(),
+            return_type: return_type,
+        };
+        self.functions.insert(name.to_string(), signature);
+    }
+
+    fn define(&mut self, name: &str, typ: DataType) {
+        self.scopes
+            .last_mut()
+            .expect("Checker always has the global scope")
+            .insert(name.to_string(), typ);
+    }
+
+    fn get(&self, name: &str) -> DataType {
+        return self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .unwrap_or(DataType::None);
+    }
+
+    fn is_variable(&self, name: &str) -> bool {
+        return self.scopes.iter().any(|scope| scope.contains_key(name));
+    }
+
+    fn check_block(&mut self, body: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        for statement in body.iter() {
+            self.check_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_statement(&mut self, statement: &Stmt) {
+        match &statement.kind {
+            StmtKind::Block(body) => self.check_block(body),
+            StmtKind::Expression(expression) => {
+                self.check_expression(expression);
+            }
+            StmtKind::FnDecl(function) => {
+                self.define_function(&function.name, &function.parameters, &function.return_type);
+                let return_type: DataType = self.functions[&function.name].return_type.clone();
+                let outer_return_type: Option<DataType> = self.return_type.replace(return_type);
+                self.scopes.push(HashMap::new());
+                for parameter in function.parameters.iter() {
+                    if let Some(default) = &parameter.default {
+                        let found: DataType = self.check_expression(default);
+                        if !is_compatible(&parameter.typ, &found) {
+                            self.error(
+                                format!(
+                                    "Expected {} but found {} for default value of parameter '{}'",
+                                    parameter.typ, found, parameter.name
+                                ),
+                                default.span(),
+                            );
+                        }
+                    }
+                    // Variadic parameters are arrays of their type
+                    let typ: DataType = match parameter.variadic {
+                        true => DataType::Array(ArrayType {
+                            elem: Box::new(parameter.typ.clone()),
+                            size: None,
+                        }),
+                        false => parameter.typ.clone(),
+                    };
+                    self.define(&parameter.name, typ);
+                }
+                self.check_block(&function.body);
+                self.scopes.pop();
+                self.return_type = outer_return_type;
+            }
+            StmtKind::If {
+                condition,
+                body,
+                elifs,
+                else_body,
+            } => {
+                self.check_condition(condition, "if");
+                self.check_block(body);
+                for (condition, body) in elifs.iter() {
+                    self.check_condition(condition, "if");
+                    self.check_block(body);
+                }
+                if let Some(body) = else_body {
+                    self.check_block(body);
+                }
+            }
+            StmtKind::Return(value) => {
+                let found: DataType = match value {
+                    Some(value) => self.check_expression(value),
+                    None => DataType::Void,
+                };
+                // Returning outside of a Function just stops the Program
+                let expected: DataType = match &self.return_type {
+                    Some(expected) => expected.clone(),
+                    None => return,
+                };
+                if !is_compatible(&expected, &found) {
+                    self.error(
+                        format!("Expected {} return value but found {}", expected, found),
+                        statement.span(),
+                    );
+                }
+            }
+            StmtKind::VarDecl(declaration) => self.check_declaration(declaration, statement.span()),
+            StmtKind::While { condition, body } => {
+                self.check_condition(condition, "while");
+                self.check_block(body);
+            }
+            StmtKind::ForIn {
+                variable,
+                iterable,
+                body,
+            } => {
+                let typ: DataType = self.check_iterable(iterable);
+                self.scopes.push(HashMap::new());
+                self.define(variable, typ);
+                self.check_block(body);
+                self.scopes.pop();
+            }
+            StmtKind::For {
+                init,
+                condition,
+                step,
+                body,
+            } => {
+                self.scopes.push(HashMap::new());
+                if let Some(init) = init {
+                    self.check_statement(init);
+                }
+                if let Some(condition) = condition {
+                    self.check_condition(condition, "for");
+                }
+                if let Some(step) = step {
+                    self.check_expression(step);
+                }
+                self.check_block(body);
+                self.scopes.pop();
+            }
+            StmtKind::Break | StmtKind::Continue | StmtKind::NoOperation => {}
+            StmtKind::EnumDecl(_) | StmtKind::StructDecl(_) => {}
+        }
+    }
+
+    // Type of the values the iterable gives, ranges give integers
+    fn check_iterable(&mut self, iterable: &Expr) -> DataType {
+        let typ: DataType = self.check_expression(iterable);
+        match (&iterable.kind, typ) {
+            (ExprKind::Binary { operator, .. }, _) if operator.starts_with("..") => {
+                DataType::Integer
+            }
+            (_, DataType::Array(array)) => *array.elem,
+            (_, DataType::None) => DataType::None,
+            (_, typ) => {
+                self.error(format!("Can not iterate over {}", typ), iterable.span());
+                DataType::None
+            }
+        }
+    }
+
+    // Variables without a type annotation get the type of their initial value
+    fn check_declaration(&mut self, declaration: &VarDecl, span: Range<usize>) {
+        let found: DataType = match &declaration.value {
+            Some(value) => {
+                let found: DataType = self.check_expression(value);
+                if !is_compatible(&declaration.typ, &found) {
+                    self.error(
+                        format!(
+                            "Expected {} but found {} for variable '{}'",
+                            declaration.typ, found, declaration.name
+                        ),
+                        value.span(),
+                    );
+                }
+                found
+            }
+            None if declaration.typ == DataType::None => {
+                self.error(
+                    format!(
+                        "Variable '{}' needs a type annotation or an initial value",
+                        declaration.name
+                    ),
+                    span,
+                );
+                DataType::None
+            }
+            None => DataType::None,
+        };
+        let typ: DataType = match &declaration.typ {
+            DataType::None => found,
+            typ => typ.clone(),
+        };
+        self.define(&declaration.name, typ);
+    }
+
+    fn check_condition(&mut self, condition: &Expr, keyword: &str) {
+        let typ: DataType = self.check_expression(condition);
+        if !is_compatible(&DataType::Boolean, &typ) {
+            self.error(
+                format!("Condition of '{}' must be bool but found {}", keyword, typ),
+                condition.span(),
+            );
+        }
+    }
+
+    fn check_expression(&mut self, expression: &Expr) -> DataType {
+        let span: Range<usize> = expression.span();
+        match &expression.kind {
+            ExprKind::Literal { typ, .. } => typ.clone(),
+            ExprKind::Identifier(name) => self.get(name),
+            ExprKind::Unary { operator, operand } => {
+                let typ: DataType = self.check_expression(operand);
+                match (operator.as_str(), &typ) {
+                    (_, DataType::None) => DataType::None,
+                    ("-", DataType::Integer | DataType::Float) => typ,
+                    ("!", DataType::Boolean | DataType::Integer) => typ,
+                    _ => self.unary_error(operator, &typ, span),
+                }
+            }
+            ExprKind::PreInc(operand) | ExprKind::PostInc(operand) => {
+                self.check_increment("++", operand, span)
+            }
+            ExprKind::PreDec(operand) | ExprKind::PostDec(operand) => {
+                self.check_increment("--", operand, span)
+            }
+            ExprKind::Binary {
+                operator,
+                left,
+                right,
+            } if ASSIGNMENT_OPERATORS.contains(&operator.as_str()) => {
+                self.check_assignment(operator, left, right, span)
+            }
+            ExprKind::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left: DataType = self.check_expression(left);
+                let right: DataType = self.check_expression(right);
+                self.check_binary(operator, left, right, span)
+            }
+            ExprKind::Ternary {
+                condition,
+                then_value,
+                else_value,
+            } => {
+                self.check_condition(condition, "?");
+                let then_type: DataType = self.check_expression(then_value);
+                let else_type: DataType = self.check_expression(else_value);
+                match (then_type, else_type) {
+                    (DataType::None, typ) | (typ, DataType::None) => typ,
+                    (then_type, else_type) if then_type == else_type => then_type,
+                    (then_type, else_type) => {
+                        self.error(
+                            format!(
+                                "Branches of '?' must have the same type but found {} and {}",
+                                then_type, else_type
+                            ),
+                            span,
+                        );
+                        DataType::None
+                    }
+                }
+            }
+            ExprKind::Grouping(expression) => self.check_expression(expression),
+            ExprKind::Call { callee, arguments } => self.check_call(callee, arguments, span),
+            // Array literals have no DataType yet, only annotated arrays do
+            ExprKind::ArrayLit(elements) => {
+                for element in elements.iter() {
+                    self.check_expression(element);
+                }
+                DataType::None
+            }
+            ExprKind::Index { target, index } => {
+                let target: DataType = self.check_expression(target);
+                self.check_expression(index);
+                match target {
+                    DataType::Array(array) => *array.elem,
+                    _ => DataType::None,
+                }
+            }
+            // Structs and enums have no DataType yet
+            ExprKind::StructLit { fields, .. } => {
+                for (_, value) in fields.iter() {
+                    self.check_expression(value);
+                }
+                DataType::None
+            }
+            ExprKind::FieldAccess { target, .. } => {
+                self.check_expression(target);
+                DataType::None
+            }
+            ExprKind::EnumVariant { .. } => DataType::None,
+            // Arms can give different types so the Match has none yet
+            ExprKind::Match { scrutinee, arms } => {
+                self.check_expression(scrutinee);
+                for arm in arms.iter() {
+                    if let Pattern::Value(value) = &arm.pattern {
+                        self.check_expression(value);
+                    }
+                    match &arm.body {
+                        ArmBody::Block(body) => self.check_block(body),
+                        ArmBody::Expression(expression) => {
+                            self.check_expression(expression);
+                        }
+                    }
+                }
+                DataType::None
+            }
+            ExprKind::Lambda {
+                parameters,
+                return_type,
+                body,
+            } => self.check_lambda(parameters, return_type, body),
+        }
+    }
+
+    // Lambdas have no DataType yet, their parameters and return values are still checked
+    fn check_lambda(
+        &mut self,
+        parameters: &[Parameter],
+        return_type: &DataType,
+        body: &LambdaBody,
+    ) -> DataType {
+        let outer_return_type: Option<DataType> = self.return_type.replace(return_type.clone());
+        self.scopes.push(HashMap::new());
+        for parameter in parameters.iter() {
+            self.define(&parameter.name, parameter.typ.clone());
+        }
+        match body {
+            LambdaBody::Block(body) => self.check_block(body),
+            LambdaBody::Expression(expression) => {
+                let found: DataType = self.check_expression(expression);
+                if !is_compatible(return_type, &found) {
+                    self.error(
+                        format!("Expected {} return value but found {}", return_type, found),
+                        expression.span(),
+                    );
+                }
+            }
+        }
+        self.scopes.pop();
+        self.return_type = outer_return_type;
+        return DataType::None;
+    }
+
+    fn unary_error(&mut self, operator: &str, typ: &DataType, span: Range<usize>) -> DataType {
+        self.error(
+            format!("Operator '{}' can not be applied to {}", operator, typ),
+            span,
+        );
+        return DataType::None;
+    }
+
+    fn check_increment(&mut self, operator: &str, operand: &Expr, span: Range<usize>) -> DataType {
+        let typ: DataType = self.check_expression(operand);
+        match typ {
+            DataType::None | DataType::Integer | DataType::Float => typ,
+            _ => self.unary_error(operator, &typ, span),
+        }
+    }
+
+    // Compound assignments like '+=' are checked like their operator first
+    fn check_assignment(
+        &mut self,
+        operator: &str,
+        target: &Expr,
+        value: &Expr,
+        span: Range<usize>,
+    ) -> DataType {
+        let expected: DataType = self.check_expression(target);
+        let mut found: DataType = self.check_expression(value);
+        if let Some(operator) = operator
+            .strip_suffix('=')
+            .filter(|operator| !operator.is_empty())
+        {
+            found = self.check_binary(operator, expected.clone(), found, span);
+        }
+        if !is_compatible(&expected, &found) {
+            self.error(
+                format!(
+                    "Can not assign {} to '{}' of type {}",
+                    found, target, expected
+                ),
+                value.span(),
+            );
+        }
+        return expected;
+    }
+
+    fn check_binary(
+        &mut self,
+        operator: &str,
+        left: DataType,
+        right: DataType,
+        span: Range<usize>,
+    ) -> DataType {
+        if left == DataType::None || right == DataType::None {
+            return DataType::None;
+        }
+        let typ: Option<DataType> = match (operator, &left) {
+            _ if left != right => None,
+            ("+", DataType::String) => Some(DataType::String),
+            ("+" | "-" | "*" | "/" | "%" | "**", DataType::Integer | DataType::Float) => {
+                Some(left.clone())
+            }
+            ("&" | "|" | "^", DataType::Integer | DataType::Boolean) => Some(left.clone()),
+            ("<<" | ">>", DataType::Integer) => Some(DataType::Integer),
+            ("&&" | "||", DataType::Boolean) => Some(DataType::Boolean),
+            ("??", _) => Some(left.clone()),
+            ("==" | "!=", _) => Some(DataType::Boolean),
+            ("<" | "<=" | ">" | ">=", DataType::Integer | DataType::Float) => {
+                Some(DataType::Boolean)
+            }
+            // Ranges have no DataType yet
+            (".." | "..=", DataType::Integer) => Some(DataType::None),
+            _ => None,
+        };
+        match typ {
+            Some(typ) => typ,
+            None => {
+                self.error(
+                    format!(
+                        "Operator '{}' can not be applied to {} and {}",
+                        operator, left, right
+                    ),
+                    span,
+                );
+                DataType::None
+            }
+        }
+    }
+
+    fn check_call(&mut self, callee: &Expr, arguments: &[Expr], span: Range<usize>) -> DataType {
+        let found: Vec<DataType> = arguments
+            .iter()
+            .map(|argument| self.check_expression(argument))
+            .collect();
+        // Variables holding lambdas shadow Functions of the same name
+        let name: &String = match &callee.kind {
+            ExprKind::Identifier(name) if !self.is_variable(name) => name,
+            _ => return DataType::None,
+        };
+        let signature: &Signature = match self.functions.get(name) {
+            Some(signature) => signature,
+            // Built-in print functions take any arguments
+            None if name == "print" || name == "println" => return DataType::Void,
+            None => return DataType::None,
+        };
+        let return_type: DataType = signature.return_type.clone();
+        let (required, maximum): (usize, Option<usize>) = get_arity(&signature.parameters);
+        if arguments.len() < required || maximum.is_some_and(|maximum| arguments.len() > maximum) {
+            let expected: usize = match arguments.len() < required {
+                true => required,
+                false => signature.parameters.len(),
+            };
+            let message: String = format!(
+                "Function '{}' expects {} arguments but found {}",
+                name,
+                expected,
+                arguments.len()
+            );
+            self.error(message, span);
+            return return_type;
+        }
+        // Every argument left for a variadic parameter has its type
+        let variadic: Option<&Parameter> = signature
+            .parameters
+            .last()
+            .filter(|parameter| parameter.variadic);
+        let parameters = signature
+            .parameters
+            .iter()
+            .chain(std::iter::repeat(variadic).map_while(|parameter| parameter));
+        let mismatches: Vec<(String, Range<usize>)> = parameters
+            .zip(found.iter())
+            .zip(arguments.iter())
+            .filter(|((parameter, found), _)| !is_compatible(&parameter.typ, found))
+            .map(|((parameter, found), argument)| {
+                let message: String = format!(
+                    "Expected {} but found {} for parameter '{}' of '{}'",
+                    parameter.typ, found, parameter.name, name
+                );
+                (message, argument.span())
+            })
+            .collect();
+        for (message, span) in mismatches {
+            self.error(message, span);
+        }
+        return return_type;
+    }
+}
+
+// Unknown types are compatible with everything
+fn is_compatible(expected: &DataType, found: &DataType) -> bool {
+    return *expected == DataType::None || *found == DataType::None || expected == found;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::generate_ast;
+    use crate::lexer::Parser;
+
+    fn check_from(code: &str) -> Vec<TypeError> {
+        let mut parser: Parser = Parser::init(code);
+        let program: Program = generate_ast(&parser.parse().unwrap()).unwrap();
+        return check_program(&program);
+    }
+
+    fn messages_from(code: &str) -> Vec<String> {
+        return check_from(code)
+            .into_iter()
+            .map(|error| error.message)
+            .collect();
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        assert_eq!(
+            check_from("let x: int = \"s\";"),
+            vec![TypeError {
+                message: "Expected int but found str for variable 'x'".to_string(),
+                span: 13..16,
+            }]
+        );
+
+        let programs: Vec<(&str, &str)> = vec![
+            (
+                "1 + 1.5;",
+                "Operator '+' can not be applied to int and float",
+            ),
+            ("-true;", "Operator '-' can not be applied to bool"),
+            (
+                "let s: str = \"\"; s++;",
+                "Operator '++' can not be applied to str",
+            ),
+            (
+                "let b: bool = true; b = 1;",
+                "Can not assign int to 'b' of type bool",
+            ),
+            (
+                "let i: int = 0; i += 0.5;",
+                "Operator '+' can not be applied to int and float",
+            ),
+            ("if 1 {}", "Condition of 'if' must be bool but found int"),
+            (
+                "let x: int = 2f;",
+                "Expected int but found float for variable 'x'",
+            ),
+            (
+                "while \"s\" {}",
+                "Condition of 'while' must be bool but found str",
+            ),
+            (
+                "fun f(a: int) -> int { return true; }",
+                "Expected int return value but found bool",
+            ),
+            (
+                "fun f() { return 1; }",
+                "Expected void return value but found int",
+            ),
+            (
+                "fun f() -> int { return; }",
+                "Expected int return value but found void",
+            ),
+            (
+                "f(1, 'c'); fun f(a: int, b: str) {}",
+                "Expected str but found char for parameter 'b' of 'f'",
+            ),
+            (
+                "fun f(a: int) {} f();",
+                "Function 'f' expects 1 arguments but found 0",
+            ),
+            (
+                "fun f(a: str, b: int...) {} f(\"\", 1, 2, 'c');",
+                "Expected int but found char for parameter 'b' of 'f'",
+            ),
+            (
+                "fun f(a: int = 1) {} f(1, 2);",
+                "Function 'f' expects 1 arguments but found 2",
+            ),
+            (
+                "fun f(a: int, b: str = 1) {} f(1);",
+                "Expected str but found int for default value of parameter 'b'",
+            ),
+            (
+                "fun f() {} let x: int = f();",
+                "Expected int but found void for variable 'x'",
+            ),
+            (
+                "|a: int| -> str { a };",
+                "Expected str return value but found int",
+            ),
+            (
+                "|| -> bool 1.5;",
+                "Expected bool return value but found float",
+            ),
+            (
+                "|s: str| s * 2;",
+                "Operator '*' can not be applied to str and int",
+            ),
+            ("for x in true {}", "Can not iterate over bool"),
+            ("1 ? 2 : 3;", "Condition of '?' must be bool but found int"),
+            // Both operands are checked even though the right one may not be evaluated
+            (
+                "false && 1;",
+                "Operator '&&' can not be applied to bool and int",
+            ),
+            ("1 || 2;", "Operator '||' can not be applied to int and int"),
+            (
+                "let x: int = true ? 1 : 2.5;",
+                "Branches of '?' must have the same type but found int and float",
+            ),
+            (
+                "let s: str = true ? 1 : 2;",
+                "Expected str but found int for variable 's'",
+            ),
+            (
+                "for x in 0..3 { let s: str = x; }",
+                "Expected str but found int for variable 's'",
+            ),
+        ];
+        for (code, message) in programs {
+            assert_eq!(messages_from(code), vec![message.to_string()], "{}", code);
+        }
+
+        // Indexing an annotated array gives its element type
+        assert_eq!(
+            messages_from(
+                "let a: [[int]] = []; let b: int = a[0]; fun f(s: str...) {} f(a[0][0]);"
+            ),
+            vec![
+                "Expected int but found [int] for variable 'b'",
+                "Expected str but found int for parameter 's' of 'f'",
+            ]
+        );
+
+        // Every error is reported
+        assert_eq!(check_from("1 + true; -\"s\"; let c: char = 1;").len(), 3);
+    }
+
+    #[test]
+    fn test_type_inference() {
+        assert_eq!(
+            check_from("let x = 1 + 2; let y: int = x; let z = y * x;"),
+            vec![]
+        );
+        assert_eq!(
+            check_from("let x = 1 + 2; x + \"s\";"),
+            vec![TypeError {
+                message: "Operator '+' can not be applied to int and str".to_string(),
+                span: 15..22,
+            }]
+        );
+
+        let programs: Vec<(&str, &str)> = vec![
+            (
+                "let s = \"a\" + \"b\"; s = 1.0;",
+                "Can not assign float to 's' of type str",
+            ),
+            (
+                "const b = 1 < 2; let c: int = b;",
+                "Expected int but found bool for variable 'c'",
+            ),
+            (
+                "fun f() -> float { return 1.0; } let v = f(); v && true;",
+                "Operator '&&' can not be applied to float and bool",
+            ),
+            (
+                "let x = true; { let x = 1; } -x;",
+                "Operator '-' can not be applied to bool",
+            ),
+            (
+                "let y;",
+                "Variable 'y' needs a type annotation or an initial value",
+            ),
+        ];
+        for (code, message) in programs {
+            assert_eq!(messages_from(code), vec![message.to_string()], "{}", code);
+        }
+
+        // Declared types are used over inferred ones and unknown values stay unknown
+        assert_eq!(
+            check_from("let a: int; a = 1; let u = [1]; u = 1.5;"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_well_typed_program() {
+        assert_eq!(check_from("let y: int = 1 + 2;"), vec![]);
+
+        let code: &str =
+            "fun fact(n: int) -> int { if n <= 1 { return 1; } return n * fact(n - 1); }
+            fun main() {
+                let x: int = fact(5);
+                let greeting: str = \"Hi \" + \"there\";
+                let flag: bool = x == 120 && !false;
+                const half: float = 0.5 * 2.0;
+                while x > 0 { x -= 1; x++; --x; }
+                print(greeting, x);
+                let square = |n: int| -> int { return n * n; };
+                let y: int = square(x) + (|| 1)();
+                return;
+            }";
+        assert_eq!(check_from(code), vec![]);
+
+        // Unknown types do not cause errors
+        assert_eq!(
+            check_from("x + 1; let y: int = [1][0]; undefined(1) + 2.5;"),
+            vec![]
+        );
+    }
+}
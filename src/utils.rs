@@ -1,23 +1,187 @@
-use crate::defs::{DataType, TokenType, DATATYPES, TOKEN_REGEXES};
-use regex::Regex;
+use crate::defs::{
+    DataType, Parameter, Token, TokenType, BINARY_OPERATORS, DATATYPES, UNARY_OPERATORS,
+};
+use std::collections::HashMap;
+use std::num::ParseIntError;
+use std::ops::Range;
 
 pub(crate) fn get_datatype_from_str(datatype_str: &str) -> DataType {
-    assert_eq!(DATATYPES.len(), 3);
+    assert_eq!(DATATYPES.len(), 6);
     match datatype_str {
+        "bool" => DataType::Boolean,
         "char" => DataType::Character,
+        "float" => DataType::Float,
         "int" => DataType::Integer,
         "str" => DataType::String,
+        "void" => DataType::Void,
         &_ => panic!("'{}' is not a valid DataType", datatype_str),
     }
 }
 
-pub(crate) fn get_token_type(token: &str) -> &TokenType {
-    for (regex, token_type) in TOKEN_REGEXES.entries() {
-        // Take match from capture group if it is explicitly specified
-        let is_match: bool = Regex::new(regex).unwrap().is_match(token);
-        if is_match {
-            return token_type;
+// Fewest and most arguments a Function with these parameters can be called with
+// Parameters with a default value can be left out and variadic ones take any number
+pub(crate) fn get_arity(parameters: &[Parameter]) -> (usize, Option<usize>) {
+    let required: usize = parameters
+        .iter()
+        .filter(|parameter| parameter.default.is_none() && !parameter.variadic)
+        .count();
+    match parameters.iter().any(|parameter| parameter.variadic) {
+        true => (required, None),
+        false => (required, Some(parameters.len())),
+    }
+}
+
+// Get the operator most likely meant by a mistyped one, preferring the same characters reordered
+// Only operators a single edit away are suggested, and never for a single character
+pub(crate) fn get_suggestion(lexeme: &str) -> Option<&'static str> {
+    if lexeme.chars().count() < 2 {
+        return None;
+    }
+    let mut sorted_lexeme: Vec<char> = lexeme.chars().collect();
+    sorted_lexeme.sort();
+
+    // Of equally close operators the first one is taken
+    return BINARY_OPERATORS
+        .iter()
+        .chain(UNARY_OPERATORS.iter())
+        .filter(|operator| get_edit_distance(lexeme, operator) == 1)
+        .min_by_key(|operator| {
+            let mut sorted_operator: Vec<char> = operator.chars().collect();
+            sorted_operator.sort();
+            (
+                sorted_operator != sorted_lexeme,
+                operator.len() != lexeme.len(),
+            )
+        })
+        .copied();
+}
+
+// Optimal string alignment distance, counting swapping two adjacent characters as one edit
+pub(crate) fn get_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<Vec<usize>> = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost: usize = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance: usize = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    return distances[a.len()][b.len()];
+}
+
+// Count how many Tokens of each TokenType there are
+pub(crate) fn token_stats(tokens: &[Token]) -> HashMap<TokenType, usize> {
+    let mut stats: HashMap<TokenType, usize> = HashMap::new();
+    for token in tokens {
+        *stats.entry(token.token_type.clone()).or_insert(0) += 1;
+    }
+    return stats;
+}
+
+// Get the Token containing the byte offset, None if the offset is in skipped whitespace
+// Tokens must be in the order they were lexed so their spans can be binary searched
+#[allow(dead_code)]
+pub(crate) fn token_at(tokens: &[Token], offset: usize) -> Option<&Token> {
+    let index: usize = tokens.partition_point(|token| token.span.end <= offset);
+    return tokens
+        .get(index)
+        .filter(|token| token.span.contains(&offset));
+}
+
+// Remove the underscores separating digits in a numeric literal
+pub(crate) fn strip_digit_separators(literal: &str) -> String {
+    literal.replace('_', "")
+}
+
+// Get the value of an integer literal in any supported radix
+// Fails on digits outside of the radix or when the value does not fit in u128
+pub(crate) fn get_integer_value(literal: &str) -> Result<u128, ParseIntError> {
+    let literal: &str = &strip_digit_separators(literal);
+    if let Some(digits) = literal.strip_prefix("0x") {
+        return u128::from_str_radix(digits, 16);
+    }
+    if let Some(digits) = literal.strip_prefix("0b") {
+        return u128::from_str_radix(digits, 2);
+    }
+    if let Some(digits) = literal.strip_prefix("0o") {
+        return u128::from_str_radix(digits, 8);
+    }
+    return literal.parse();
+}
+
+// Replace escape sequences with the characters they represent
+// Returns the byte range of the first invalid escape sequence on failure
+pub(crate) fn unescape(content: &str) -> Result<String, Range<usize>> {
+    let mut unescaped: String = String::with_capacity(content.len());
+    let mut index: usize = 0;
+    while let Some(character) = content[index..].chars().next() {
+        if character != '\\' {
+            unescaped.push(character);
+            index += character.len_utf8();
+            continue;
+        }
+        match get_escaped_character(&content[index..]) {
+            Ok((escaped, length)) => {
+                unescaped.push(escaped);
+                index += length;
+            }
+            Err(length) => return Err(index..index + length),
         }
     }
-    panic!("Did not get TokenType for '{}'", token);
+    return Ok(unescaped);
+}
+
+// Decode the escape sequence at the start of the code into a character and its length
+// Returns the length of the invalid escape sequence on failure
+fn get_escaped_character(code: &str) -> Result<(char, usize), usize> {
+    let escaped: char = match code[1..].chars().next() {
+        Some(escaped) => escaped,
+        None => return Err(1),
+    };
+    let character: char = match escaped {
+        'n' => '\n',
+        't' => '\t',
+        '\\' => '\\',
+        '"' => '"',
+        '\'' => '\'',
+        '0' => '\0',
+        'u' => return get_unicode_character(code),
+        _ => return Err(1 + escaped.len_utf8()),
+    };
+    return Ok((character, 2));
+}
+
+fn get_unicode_character(code: &str) -> Result<(char, usize), usize> {
+    // Unicode escapes have 1 to 6 hexadecimal digits in braces, e.g. \u{1F600}
+    if !code[2..].starts_with('{') {
+        return Err(2);
+    }
+    let length: usize = match code.find('}') {
+        Some(index) => index + 1,
+        None => return Err(code.len()),
+    };
+    let digits: &str = &code[3..length - 1];
+    if digits.is_empty() || digits.len() > 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(length);
+    }
+
+    // Code point must be a Unicode scalar value
+    let code_point: u32 = u32::from_str_radix(digits, 16).unwrap();
+    match char::from_u32(code_point) {
+        Some(character) => Ok((character, length)),
+        None => Err(length),
+    }
 }
@@ -0,0 +1,125 @@
+// Explicit returns are the house style
+#![allow(clippy::needless_return)]
+
+use std::io::Write;
+use std::process::{Child, Command, Output, Stdio};
+
+// Run pog with the given arguments, piping the input to its stdin
+fn run_pog(args: &[&str], input: &str) -> Output {
+    let mut child: Child = Command::new(env!("CARGO_BIN_EXE_pog"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start pog");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    return child.wait_with_output().unwrap();
+}
+
+#[test]
+fn test_reading_stdin() {
+    let output: Output = run_pog(&["-"], "42; x = 1+2;");
+    assert!(output.status.success());
+    let ast: String = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(ast, "42;\n(x = (1 + 2));\n");
+}
+
+#[test]
+fn test_reading_empty_stdin() {
+    let output: Output = run_pog(&["-"], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_repl() {
+    let output: Output = run_pog(&["repl"], "42;\n@\nfun ;\nx + 1;\n");
+    assert!(output.status.success());
+
+    // Errors are reported and the following lines are still parsed
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    let stderr: String = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("42;\n"), "{}", stdout);
+    assert!(stdout.contains("(x + 1);\n"), "{}", stdout);
+    assert!(stderr.contains("Unknown Token"), "{}", stderr);
+    assert!(
+        stderr.contains("Expected Identifier but found ';'"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_repl_without_arguments() {
+    let output: Output = run_pog(&[], "1;\n");
+    assert!(output.status.success());
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1;\n"), "{}", stdout);
+}
+
+#[test]
+fn test_reporting_every_parse_error() {
+    let output: Output = run_pog(&["-"], "let a = ;\nlet b = 1;\nreturn * 2;\n");
+    assert!(!output.status.success());
+    let stderr: String = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 1: let a = ;"), "{}", stderr);
+    assert!(stderr.contains("line 3: return * 2;"), "{}", stderr);
+}
+
+#[test]
+fn test_reporting_type_errors() {
+    let output: Output = run_pog(&["-"], "let x: int = \"s\";\nlet y: int = 1 + 2;\n");
+    assert!(!output.status.success());
+    let stderr: String = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Expected int but found str for variable 'x'\nline 1: let x: int = \"s\";"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("line 2"), "{}", stderr);
+}
+
+#[test]
+fn test_running_program() {
+    let code: &str =
+        "fun add(a: int, b: int) -> int { return a + b; }\nprintln(add(1, 2));\nprint(\"x\");";
+    let output: Output = run_pog(&["--run", "-"], code);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "3\nx");
+
+    // Runtime errors point at where they happened
+    let output: Output = run_pog(&["--run", "-"], "println(1);\nlet x: int = 1 / 0;\n");
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1\n");
+    let stderr: String = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 2: let x: int = 1 / 0;"), "{}", stderr);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_dumping_ast_json() {
+    let output: Output = run_pog(&["--ast-json", "-"], "break;");
+    assert!(output.status.success());
+    let json: String = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        json,
+        "{\"statements\":[{\"kind\":\"Break\",\"span\":{\"start\":0,\"end\":6}}]}\n"
+    );
+}
+
+#[test]
+fn test_printing_token_stats() {
+    let output: Output = run_pog(&["--stats", "-"], "x = 1 + 2;");
+    assert!(output.status.success());
+    let stats: String = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stats,
+        "binary operator: 2\ninteger literal: 2\ndelimiter: 1\nidentifier: 1\n"
+    );
+}